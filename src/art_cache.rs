@@ -0,0 +1,81 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Oldest files are removed once the cache holds more than this many.
+const MAX_ENTRIES: usize = 200;
+
+fn cache_dir() -> PathBuf {
+    glib::user_cache_dir().join("empress").join("art")
+}
+
+/// Where the art for `url` is kept, named by a hash of the whole URL. The
+/// query is part of the key, since servers like Subsonic name the cover
+/// there (`getCoverArt.view?id=…`).
+fn path_for(url: &str) -> Option<PathBuf> {
+    let hash = glib::compute_checksum_for_string(glib::ChecksumType::Sha256, url)?;
+    Some(cache_dir().join(hash.as_str()))
+}
+
+/// Where the art downloaded from `url` is kept, if it has been.
+pub fn cached_path(url: &str) -> Option<PathBuf> {
+    path_for(url).filter(|path| path.is_file())
+}
+
+/// Image bytes previously downloaded from `url`, if any.
+pub fn load(url: &str) -> Option<Vec<u8>> {
+    fs::read(path_for(url)?).ok()
+}
+
+/// Keep `bytes` downloaded from `url` for next time, making room first if
+/// the cache is full.
+pub fn store(url: &str, bytes: &[u8]) -> anyhow::Result<()> {
+    let Some(path) = path_for(url) else {
+        return Ok(());
+    };
+    let dir = cache_dir();
+    fs::create_dir_all(&dir)?;
+    prune(&dir);
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Make room for one more file by removing the oldest ones.
+fn prune(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((modified, entry.path()))
+        })
+        .collect();
+    if files.len() < MAX_ENTRIES {
+        return;
+    }
+
+    files.sort();
+    for (_, path) in &files[..=files.len() - MAX_ENTRIES] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn covers_named_in_the_query_get_their_own_entries() {
+        let first = path_for("https://music.example/rest/getCoverArt.view?id=al-1");
+        assert!(first.is_some());
+        assert_ne!(
+            path_for("https://music.example/rest/getCoverArt.view?id=al-2"),
+            first
+        );
+        assert_eq!(
+            path_for("https://music.example/rest/getCoverArt.view?id=al-1"),
+            first
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::APP_ID;
+
+/// Command-line flag that starts Empress with its window minimized.
+pub const START_MINIMIZED_FLAG: &str = "start-minimized";
+
+/// Location of our XDG autostart entry.
+pub fn path() -> PathBuf {
+    glib::user_config_dir()
+        .join("autostart")
+        .join(format!("{}.desktop", APP_ID))
+}
+
+pub fn is_enabled() -> bool {
+    path().exists()
+}
+
+/// Create or remove the autostart entry. When enabling, `start_minimized`
+/// adds `--start-minimized` so Empress comes up as a background controller.
+pub fn set_enabled(enabled: bool, start_minimized: bool) -> anyhow::Result<()> {
+    let path = path();
+
+    if !enabled {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        return Ok(());
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, desktop_entry(start_minimized))?;
+    Ok(())
+}
+
+fn desktop_entry(start_minimized: bool) -> String {
+    // Inside the Flatpak sandbox the binary isn't on the host's PATH
+    let mut exec = if Path::new("/.flatpak-info").exists() {
+        format!("flatpak run {}", APP_ID)
+    } else {
+        "empress".to_string()
+    };
+    if start_minimized {
+        exec.push_str(&format!(" --{}", START_MINIMIZED_FLAG));
+    }
+
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Empress\n\
+         Comment=Control media players from the background\n\
+         Exec={}\n\
+         Icon={}\n\
+         Terminal=false\n\
+         X-GNOME-Autostart-enabled=true\n",
+        exec, APP_ID
+    )
+}
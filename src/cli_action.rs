@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use crate::mpris_client::{CommandError, MprisClient};
+
+/// How long to wait for the player to carry out the command.
+const ACTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A command given with `--action`, run without opening a window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    PlayPause,
+    Next,
+    Previous,
+    Stop,
+    /// Seek by this many seconds, back if negative.
+    Seek(i64),
+}
+
+impl Action {
+    /// Parse `play-pause`, `next`, `previous`, `stop` or `seek +N` / `seek -N`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut words = text.split_whitespace();
+        let action = match (words.next(), words.next()) {
+            (Some("play-pause"), None) => Action::PlayPause,
+            (Some("next"), None) => Action::Next,
+            (Some("previous" | "prev"), None) => Action::Previous,
+            (Some("stop"), None) => Action::Stop,
+            (Some("seek"), Some(offset)) => offset
+                .parse()
+                .map(Action::Seek)
+                .map_err(|_| format!("Not a number of seconds: {}", offset))?,
+            _ => {
+                return Err(format!(
+                    "Unknown action \"{}\"; expected play-pause, next, previous, stop or seek +N",
+                    text
+                ))
+            }
+        };
+        match words.next() {
+            Some(extra) => Err(format!("Unexpected \"{}\" after the action", extra)),
+            None => Ok(action),
+        }
+    }
+}
+
+/// Send `action` to the player `client` picks and wait until it's been
+/// carried out.
+pub fn run(client: &MprisClient, action: Action) -> Result<(), String> {
+    let results = client
+        .take_command_results()
+        .ok_or("Command results already taken")?;
+
+    let sent = match action {
+        Action::PlayPause => client.play_pause(),
+        Action::Next => client.next(),
+        Action::Previous => client.previous(),
+        Action::Stop => client.stop(),
+        Action::Seek(seconds) => match seconds.checked_mul(1_000_000) {
+            Some(offset) => client.seek(offset),
+            None => return Err("Seek offset too large".to_string()),
+        },
+    };
+    sent.map_err(|e| e.to_string())?;
+
+    let outcome = results
+        .recv_timeout(ACTION_TIMEOUT)
+        .map_err(|_| "The player didn't respond".to_string())?;
+    outcome.result.map_err(|e| match e {
+        CommandError::NoPlayer(_) => "No media player is running".to_string(),
+        CommandError::Unsupported(action) => format!("The player can't {}", action),
+        CommandError::Failed(action, e) => format!("Couldn't {}: {}", action, e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_parse() {
+        assert_eq!(Action::parse("play-pause"), Ok(Action::PlayPause));
+        assert_eq!(Action::parse("prev"), Ok(Action::Previous));
+        assert_eq!(Action::parse("seek +30"), Ok(Action::Seek(30)));
+        assert_eq!(Action::parse("seek -5"), Ok(Action::Seek(-5)));
+        assert!(Action::parse("seek").is_err());
+        assert!(Action::parse("seek soon").is_err());
+        assert!(Action::parse("next please").is_err());
+        assert!(Action::parse("rewind").is_err());
+    }
+}
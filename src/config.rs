@@ -0,0 +1,495 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::util::write_atomically;
+
+const MIN_ART_SIZE: i32 = 64;
+const MAX_ART_SIZE: i32 = 512;
+pub const DEFAULT_ART_SIZE: i32 = 180;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Keep the album art area at a constant size, showing the placeholder
+    /// when there is no cover, so track changes never resize the window.
+    pub fixed_art_area: bool,
+    /// Fade the progress ring while playback is paused.
+    pub dim_ring_when_paused: bool,
+    /// Draw the progress ring as the time remaining, emptying as the track
+    /// plays, instead of filling up.
+    pub countdown_ring: bool,
+    /// Leave out the faint track behind the progress ring while there's no
+    /// progress yet, for a cleaner look.
+    pub hide_empty_ring_track: bool,
+    /// Thickness of the progress ring in pixels, from 1 to 8.
+    pub ring_width: f64,
+    /// Color of the progress ring, as a CSS color such as `#e01b24`. Unset
+    /// follows the theme's accent color.
+    pub ring_color: Option<String>,
+    /// Show a blurred, faded copy of the album art behind the window's
+    /// contents instead of a solid background.
+    pub art_backdrop: bool,
+    /// Show the album art in grayscale while playback is paused.
+    pub desaturate_art_when_paused: bool,
+    /// Requested album art size in pixels; see `art_size()`.
+    pub art_size_px: i32,
+    /// Show title, artist and album on a single line instead of three.
+    pub compact_metadata: bool,
+    /// What a single click on an empty part of the window does.
+    pub background_click: BackgroundClickAction,
+    /// Volume (0.0–1.0) to set the first time Empress connects to each
+    /// player. Unset leaves the player's own level alone.
+    pub default_volume: Option<f64>,
+    /// Players to pick in Auto mode when several are running, by name
+    /// (e.g. "Spotify"), most preferred first. Players not listed are
+    /// only picked when none of these are running.
+    pub player_priority: Vec<String>,
+    /// Fade the volume out over this many milliseconds when pausing from
+    /// Empress, and back in when resuming. 0 pauses immediately.
+    pub pause_fade_ms: u64,
+    /// Pause the player when the screen locks or the system suspends.
+    pub pause_on_lock: bool,
+    /// With `pause_on_lock`, start playing again once the session is
+    /// unlocked, if it was Empress that paused it.
+    pub resume_on_unlock: bool,
+    /// When to show which player the track is from, as a caption under the
+    /// artist and album.
+    pub player_identity_caption: PlayerIdentityCaption,
+    /// Start minimized when launched at login.
+    pub autostart_minimized: bool,
+    /// Hide the window instead of quitting when it's closed, keeping
+    /// Empress running in the background. Launching it again brings the
+    /// window back; Quit in the menu (or Ctrl+Q) exits for real.
+    pub hide_on_close: bool,
+    /// Background behind the album art, as a CSS color such as `#202020`
+    /// or `transparent`. Unset uses the theme's shade color.
+    pub art_background: Option<String>,
+    /// Put the previous/play/next controls over the bottom of the album
+    /// art instead of below it.
+    pub overlay_controls: bool,
+    /// Experimental: with `overlay_controls`, show the controls as a small
+    /// cluster next to the pointer while it's over the art, instead of a
+    /// bar along the bottom.
+    pub controls_follow_cursor: bool,
+    /// Show a desktop notification with the title, artist and art when
+    /// the track changes while Empress isn't focused.
+    pub track_notifications: bool,
+    /// Show a seek bar under the time label.
+    pub show_seek_bar: bool,
+    /// Seconds to seek per scroll step over the controls.
+    pub scroll_seek_seconds: u64,
+    /// Seconds to seek per scroll step while Shift is held.
+    pub scroll_seek_shift_seconds: u64,
+    /// Scrolling down seeks forward instead of back.
+    pub natural_scroll: bool,
+    /// Show the playback position and track in the window title, updated
+    /// once a second.
+    pub position_in_title: bool,
+    /// Show how much of the player's playlist is left in Player Details,
+    /// for players that expose their track list.
+    pub show_playlist_remaining: bool,
+    /// Keep the Recently Played list in the cache directory so it
+    /// survives restarts, instead of only for the session.
+    pub persist_play_history: bool,
+    /// Larger text and controls with higher contrast.
+    pub accessible_mode: bool,
+    /// Use the current album art as the window icon, for taskbars and
+    /// window switchers that show it.
+    pub art_as_window_icon: bool,
+    /// Controls to show, in order: any of "prev", "play", "next", "stop",
+    /// "restart", "shuffle", "repeat", "rate" (playback speed) and "volume". See `controls()`.
+    pub controls: Vec<String>,
+    /// Metadata keys to take cover art from, tried in order until one holds
+    /// a URL, path, `data:` URI or base64 image. Add player-specific keys
+    /// after `mpris:artUrl` for players that put art elsewhere.
+    pub art_metadata_keys: Vec<String>,
+    /// Only load new cover art once its URL has stayed the same for this
+    /// many milliseconds, for players that flip between URLs. 0 loads it
+    /// straight away.
+    pub art_debounce_ms: u64,
+    /// File to keep a one-line playback summary in, for status bars that
+    /// read from files. Unset writes nothing.
+    pub status_file: Option<PathBuf>,
+    /// Line to write to `status_file`. Placeholders: `{icon}`, `{status}`,
+    /// `{title}`, `{artist}`, `{album}`, `{player}`, `{position}` and
+    /// `{length}`.
+    pub status_file_format: String,
+    /// File to record listens to, as a ListenBrainz `import` submission
+    /// (JSON), for syncing to ListenBrainz or last.fm with other tools. A
+    /// track counts once half of it, or four minutes, has played. Unset
+    /// records nothing.
+    pub listen_log: Option<PathBuf>,
+    /// How to add the current position to a track URL for "Copy Link at
+    /// Current Time". The first entry whose `contains` matches is used.
+    pub timestamp_links: Vec<TimestampLink>,
+    /// Tray menu actions that also bring up the window: any of "play",
+    /// "next" and "prev". Clicking the track title in the menu always does.
+    pub tray_raise_on: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TimestampLink {
+    /// Substring the track URL must contain, e.g. `youtube.com/watch`.
+    pub contains: String,
+    /// Link to copy, with `{url}` and `{seconds}` filled in.
+    pub template: String,
+}
+
+impl TimestampLink {
+    fn new(contains: &str, template: &str) -> Self {
+        Self {
+            contains: contains.to_string(),
+            template: template.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackgroundClickAction {
+    #[default]
+    None,
+    PlayPause,
+    /// Ask the player to bring its own window to the front.
+    Raise,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Control {
+    Previous,
+    PlayPause,
+    Next,
+    Stop,
+    Restart,
+    Shuffle,
+    Repeat,
+    Rate,
+    Volume,
+}
+
+impl Control {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "prev" | "previous" => Some(Control::Previous),
+            "play" | "play_pause" => Some(Control::PlayPause),
+            "next" => Some(Control::Next),
+            "stop" => Some(Control::Stop),
+            "restart" => Some(Control::Restart),
+            "shuffle" => Some(Control::Shuffle),
+            "repeat" => Some(Control::Repeat),
+            "rate" | "speed" => Some(Control::Rate),
+            "volume" => Some(Control::Volume),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlayerIdentityCaption {
+    #[default]
+    Never,
+    Always,
+    /// Only when more than one player is running.
+    MultiplePlayers,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            fixed_art_area: false,
+            dim_ring_when_paused: false,
+            countdown_ring: false,
+            hide_empty_ring_track: false,
+            ring_width: 3.0,
+            ring_color: None,
+            art_backdrop: false,
+            desaturate_art_when_paused: false,
+            art_size_px: DEFAULT_ART_SIZE,
+            compact_metadata: false,
+            background_click: BackgroundClickAction::None,
+            default_volume: None,
+            player_priority: Vec::new(),
+            pause_fade_ms: 0,
+            pause_on_lock: false,
+            resume_on_unlock: false,
+            player_identity_caption: PlayerIdentityCaption::Never,
+            autostart_minimized: true,
+            hide_on_close: false,
+            art_background: None,
+            overlay_controls: false,
+            controls_follow_cursor: false,
+            track_notifications: true,
+            show_seek_bar: false,
+            scroll_seek_seconds: 5,
+            scroll_seek_shift_seconds: 30,
+            natural_scroll: false,
+            position_in_title: false,
+            show_playlist_remaining: false,
+            persist_play_history: false,
+            accessible_mode: false,
+            art_as_window_icon: false,
+            controls: ["shuffle", "prev", "play", "next", "repeat", "volume"]
+                .iter()
+                .map(|c| c.to_string())
+                .collect(),
+            art_metadata_keys: vec!["mpris:artUrl".to_string()],
+            art_debounce_ms: 0,
+            status_file: None,
+            status_file_format: "{icon} {title} - {artist}".to_string(),
+            listen_log: None,
+            timestamp_links: vec![
+                TimestampLink::new("youtube.com/watch", "{url}&t={seconds}"),
+                TimestampLink::new("youtu.be/", "{url}?t={seconds}"),
+                TimestampLink::new("soundcloud.com/", "{url}#t={seconds}"),
+            ],
+            tray_raise_on: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn path() -> PathBuf {
+        glib::user_config_dir().join("empress").join("config.toml")
+    }
+
+    /// Album art size, clamped to something the layout can handle.
+    pub fn art_size(&self) -> i32 {
+        self.art_size_px.clamp(MIN_ART_SIZE, MAX_ART_SIZE)
+    }
+
+    /// The configured controls, skipping unknown names and duplicates.
+    /// Play/pause is always included, since without it there's no way to
+    /// control playback at all.
+    pub fn controls(&self) -> Vec<Control> {
+        let mut controls: Vec<Control> = Vec::new();
+        for name in &self.controls {
+            match Control::from_name(name) {
+                Some(control) if !controls.contains(&control) => controls.push(control),
+                Some(_) => {}
+                None => eprintln!("Ignoring unknown control in config: {}", name),
+            }
+        }
+
+        if !controls.contains(&Control::PlayPause) {
+            // Right after "prev" if present, so it stays between prev and next
+            let index = controls
+                .iter()
+                .position(|c| *c == Control::Previous)
+                .map_or(0, |i| i + 1);
+            controls.insert(index, Control::PlayPause);
+        }
+        controls
+    }
+
+    /// `url` with `position` added by the first matching template, or
+    /// unchanged when none match.
+    pub fn timestamped_link(&self, url: &str, position: Duration) -> String {
+        match self
+            .timestamp_links
+            .iter()
+            .find(|link| url.contains(&link.contains))
+        {
+            Some(link) => link
+                .template
+                .replace("{url}", url)
+                .replace("{seconds}", &position.as_secs().to_string()),
+            None => url.to_string(),
+        }
+    }
+
+    /// Load the config file, falling back to defaults when it is missing
+    /// or can't be parsed.
+    pub fn load() -> Self {
+        let path = Self::path();
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+
+        match toml::from_str(&contents) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Failed to parse config {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Write the settings to the config file at `path`. Only the keys whose
+    /// values differ from what the file already says are touched, so the
+    /// user's comments, ordering and left-out defaults survive.
+    fn save_to(&self, path: &Path) -> anyhow::Result<()> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let mut document: toml_edit::DocumentMut = contents.parse()?;
+        let current: Config = toml::from_str(&contents)?;
+
+        let table = |config: &Config| match toml::Value::try_from(config)? {
+            toml::Value::Table(table) => Ok(table),
+            _ => anyhow::bail!("config didn't serialize to a table"),
+        };
+        let (old, new) = (table(&current)?, table(self)?);
+        for (key, value) in &new {
+            if old.get(key) != Some(value) {
+                let value: toml_edit::Value = value.to_string().parse()?;
+                document[key.as_str()] = toml_edit::value(value);
+            }
+        }
+        // Options set back to nothing
+        for key in old.keys().filter(|key| !new.contains_key(*key)) {
+            document.remove(key);
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        write_atomically(path, document.to_string())?;
+        Ok(())
+    }
+
+    /// Load the config file, change it with `f`, and save it again, logging
+    /// any failure. Reloading first keeps edits made to the file while
+    /// Empress was running. A file that doesn't parse is left alone rather
+    /// than replaced with the defaults.
+    pub fn update(f: impl FnOnce(&mut Config)) {
+        Self::update_at(&Self::path(), f);
+    }
+
+    fn update_at(path: &Path, f: impl FnOnce(&mut Config)) {
+        let mut config = match fs::read_to_string(path) {
+            Ok(contents) => match toml::from_str(&contents) {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("Not saving settings, {} has errors: {}", path.display(), e);
+                    return;
+                }
+            },
+            Err(_) => Self::default(),
+        };
+        f(&mut config);
+        if let Err(e) = config.save_to(path) {
+            eprintln!("Failed to save config to {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_controls(names: &[&str]) -> Config {
+        Config {
+            controls: names.iter().map(|n| n.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn missing_keys_use_the_defaults() {
+        let config: Config = toml::from_str("natural_scroll = true").unwrap();
+        assert!(config.natural_scroll);
+        assert_eq!(config.scroll_seek_seconds, 5);
+        assert!(config.track_notifications);
+
+        let empty: Config = toml::from_str("").unwrap();
+        assert!(!empty.natural_scroll);
+    }
+
+    /// Where the config file goes under a fresh, empty `$XDG_CONFIG_HOME`.
+    fn temp_config_path(name: &str) -> PathBuf {
+        let config_home =
+            std::env::temp_dir().join(format!("empress-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&config_home);
+        config_home.join("empress").join("config.toml")
+    }
+
+    #[test]
+    fn saved_config_loads_back() {
+        let path = temp_config_path("loads-back");
+        let config = Config {
+            scroll_seek_seconds: 10,
+            player_priority: vec!["Spotify".to_string()],
+            ..Default::default()
+        };
+        config.save_to(&path).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let loaded: Config = toml::from_str(&contents).unwrap();
+        assert_eq!(loaded.scroll_seek_seconds, 10);
+        assert_eq!(loaded.player_priority, ["Spotify"]);
+        assert_eq!(loaded.timestamp_links.len(), config.timestamp_links.len());
+        // Settings left at their defaults aren't written out
+        assert!(!contents.contains("track_notifications"));
+    }
+
+    #[test]
+    fn updating_keeps_comments_and_other_keys() {
+        let path = temp_config_path("keeps-comments");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let original =
+            "# Mine\nnatural_scroll = true # like a touchpad\nring_color = \"#ff0000\"\n";
+        fs::write(&path, original).unwrap();
+
+        Config::update_at(&path, |config| config.scroll_seek_seconds = 10);
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with(original));
+        let loaded: Config = toml::from_str(&contents).unwrap();
+        assert_eq!(loaded.scroll_seek_seconds, 10);
+        assert!(loaded.natural_scroll);
+
+        Config::update_at(&path, |config| config.ring_color = None);
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with("# Mine\nnatural_scroll = true # like a touchpad\n"));
+        assert!(!contents.contains("ring_color"));
+        assert!(!path.with_extension("tmp").exists());
+    }
+
+    #[test]
+    fn updating_leaves_a_broken_file_alone() {
+        let path = temp_config_path("broken");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "natural_scroll = \n").unwrap();
+
+        Config::update_at(&path, |config| config.natural_scroll = true);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "natural_scroll = \n");
+    }
+
+    #[test]
+    fn unknown_and_duplicate_controls_are_dropped() {
+        let config = with_controls(&["prev", "play", "bogus", "next", "prev"]);
+        assert_eq!(
+            config.controls(),
+            vec![Control::Previous, Control::PlayPause, Control::Next]
+        );
+    }
+
+    #[test]
+    fn play_pause_is_always_present() {
+        assert_eq!(with_controls(&[]).controls(), vec![Control::PlayPause]);
+        assert_eq!(
+            with_controls(&["prev", "next"]).controls(),
+            vec![Control::Previous, Control::PlayPause, Control::Next]
+        );
+    }
+
+    #[test]
+    fn timestamped_link_uses_first_matching_template() {
+        let config = Config::default();
+        assert_eq!(
+            config.timestamped_link(
+                "https://www.youtube.com/watch?v=abc",
+                Duration::from_secs(83)
+            ),
+            "https://www.youtube.com/watch?v=abc&t=83"
+        );
+        assert_eq!(
+            config.timestamped_link("https://example.com/song.mp3", Duration::from_secs(83)),
+            "https://example.com/song.mp3"
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::mpris_client::{MediaInfo, PlayerStatus};
+use crate::util::write_atomically;
+
+/// A track counts as listened to once this much of it has played, or half
+/// of it if that's sooner (the usual scrobbling rule).
+const LISTEN_THRESHOLD: Duration = Duration::from_secs(4 * 60);
+/// Tracks shorter than this are never counted.
+const MIN_TRACK_LENGTH: Duration = Duration::from_secs(30);
+/// Position jumps bigger than this between polls are seeks, not playback.
+const MAX_PLAYED_STEP: Duration = Duration::from_secs(5);
+
+/// A listen in the form ListenBrainz's `submit-listens` API takes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Listen {
+    /// When the track started playing, in seconds since the Unix epoch.
+    pub listened_at: u64,
+    pub track_metadata: TrackMetadata,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrackMetadata {
+    pub artist_name: String,
+    pub track_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_name: Option<String>,
+    #[serde(default)]
+    pub additional_info: AdditionalInfo,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AdditionalInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_player: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+/// The whole file: a ListenBrainz `import` submission, so it can be
+/// POSTed as is.
+#[derive(Serialize, Deserialize)]
+struct Submission {
+    listen_type: String,
+    payload: Vec<Listen>,
+}
+
+/// Works out from successive polls when a track has been listened to.
+#[derive(Default)]
+struct ListenTracker {
+    track: Option<(String, String)>,
+    started_at: u64,
+    played: Duration,
+    last_position: Option<Duration>,
+    counted: bool,
+}
+
+impl ListenTracker {
+    /// Feed in the latest poll. Returns the listen the first time the
+    /// current track passes the threshold.
+    fn observe(&mut self, info: &MediaInfo, now: u64) -> Option<Listen> {
+        if info.player_identity.is_empty() || info.title.trim().is_empty() {
+            *self = Self::default();
+            return None;
+        }
+
+        let track = (info.title.clone(), info.artist.clone());
+        if self.track.as_ref() != Some(&track) {
+            *self = Self {
+                track: Some(track),
+                started_at: now,
+                ..Self::default()
+            };
+        }
+
+        // Only count time the position actually moved forward by playing
+        if let (Some(last), Some(position)) = (self.last_position, info.position) {
+            let step = position.saturating_sub(last);
+            if info.status == PlayerStatus::Playing && step <= MAX_PLAYED_STEP {
+                self.played += step;
+            }
+        }
+        self.last_position = info.position;
+
+        if self.counted || info.length.is_some_and(|length| length < MIN_TRACK_LENGTH) {
+            return None;
+        }
+        let threshold = info.length.map_or(LISTEN_THRESHOLD, |length| {
+            (length / 2).min(LISTEN_THRESHOLD)
+        });
+        if self.played < threshold {
+            return None;
+        }
+
+        self.counted = true;
+        Some(Listen {
+            listened_at: self.started_at,
+            track_metadata: TrackMetadata {
+                artist_name: info.artist.clone(),
+                track_name: info.title.clone(),
+                release_name: Some(info.album.clone()).filter(|album| !album.is_empty()),
+                additional_info: AdditionalInfo {
+                    media_player: Some(info.player_identity.clone()),
+                    duration_ms: info.length.map(|length| length.as_millis() as u64),
+                },
+            },
+        })
+    }
+}
+
+/// Listens recorded to a JSON file in ListenBrainz's submission format,
+/// for syncing by hand or with an external tool.
+pub struct ListenLog {
+    path: PathBuf,
+    tracker: ListenTracker,
+}
+
+impl ListenLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            tracker: ListenTracker::default(),
+        }
+    }
+
+    /// Append a listen to the file if `info` completes one.
+    pub fn update(&mut self, info: &MediaInfo) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let Some(listen) = self.tracker.observe(info, now) else {
+            return;
+        };
+        if let Err(e) = self.append(listen) {
+            eprintln!("Failed to write listen to {}: {}", self.path.display(), e);
+        }
+    }
+
+    fn append(&self, listen: Listen) -> anyhow::Result<()> {
+        let mut submission = match fs::read_to_string(&self.path) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(_) => Submission {
+                listen_type: "import".to_string(),
+                payload: Vec::new(),
+            },
+        };
+        submission.payload.push(listen);
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        write_atomically(&self.path, serde_json::to_string_pretty(&submission)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(position: u64) -> MediaInfo {
+        MediaInfo {
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            player_identity: "Player".to_string(),
+            status: PlayerStatus::Playing,
+            position: Some(Duration::from_secs(position)),
+            length: Some(Duration::from_secs(100)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn listen_is_counted_once_past_half() {
+        let mut tracker = ListenTracker::default();
+        let mut listens = 0;
+        for position in 0..=60 {
+            listens += tracker.observe(&at(position), 1000).into_iter().count();
+            // Counted as soon as 50 seconds have played
+            assert_eq!(listens, usize::from(position >= 50));
+        }
+    }
+
+    #[test]
+    fn seeking_ahead_does_not_count() {
+        let mut tracker = ListenTracker::default();
+        assert_eq!(tracker.observe(&at(0), 1000), None);
+        assert_eq!(tracker.observe(&at(90), 1000), None);
+        assert_eq!(tracker.observe(&at(91), 1000), None);
+    }
+}
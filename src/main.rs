@@ -1,26 +1,144 @@
+mod art_cache;
+mod autostart;
+mod cli_action;
+mod config;
+mod listen_log;
 mod mpris_client;
+mod play_history;
+mod player_entry;
 mod progress_ring_button;
+mod session_watch;
+mod share_card;
+mod state;
+mod status_file;
+#[cfg(feature = "tray")]
+mod tray;
 mod ui;
+mod util;
+mod window_icon;
 
+use gtk::glib;
 use gtk::prelude::*;
 use libadwaita as adw;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 
 const APP_ID: &str = "com.github.toasterrepair.empress";
+const PLAYER_OPTION: &str = "player";
+const LIST_PLAYERS_OPTION: &str = "list-players";
+const ACTION_OPTION: &str = "action";
 
 fn main() {
     let app = adw::Application::builder().application_id(APP_ID).build();
 
-    app.connect_activate(|app| {
+    app.add_main_option(
+        autostart::START_MINIMIZED_FLAG,
+        glib::Char::from(0),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        "Start with the window minimized",
+        None,
+    );
+    app.add_main_option(
+        PLAYER_OPTION,
+        glib::Char::from(b'p'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Control this player (by name, e.g. Spotify) instead of picking one",
+        Some("NAME"),
+    );
+    app.add_main_option(
+        LIST_PLAYERS_OPTION,
+        glib::Char::from(0),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        "List the names of running players and exit",
+        None,
+    );
+    app.add_main_option(
+        ACTION_OPTION,
+        glib::Char::from(0),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Send play-pause, next, previous, stop or \"seek +N\" to the player and exit",
+        Some("ACTION"),
+    );
+
+    let start_minimized = Rc::new(Cell::new(false));
+    let startup_player: Rc<RefCell<Option<String>>> = Rc::default();
+    app.connect_handle_local_options({
+        let start_minimized = start_minimized.clone();
+        let startup_player = startup_player.clone();
+        move |_, options| {
+            if options.contains(LIST_PLAYERS_OPTION) {
+                for player in mpris_client::MprisClient::get_available_players() {
+                    println!("{}", player.identity);
+                }
+                return 0;
+            }
+            let player = options.lookup::<String>(PLAYER_OPTION).ok().flatten();
+            if let Ok(Some(action)) = options.lookup::<String>(ACTION_OPTION) {
+                return run_action(&action, player);
+            }
+            start_minimized.set(options.contains(autostart::START_MINIMIZED_FLAG));
+            startup_player.replace(player);
+            // Carry on with the default handling
+            -1
+        }
+    });
+
+    app.connect_activate(move |app| {
+        // Launching again while the window is hidden on close brings it back
+        if let Some(window) = app.windows().first() {
+            window.present();
+            return;
+        }
+
         // Load custom CSS
         load_css();
 
-        let window = ui::build_ui(app);
+        let config = config::Config::load();
+        load_config_css(&config);
+        let window = ui::build_ui(app, &config, startup_player.take());
+        // Minimizing before the first present keeps the window from
+        // flashing up at login
+        if start_minimized.replace(false) {
+            window.minimize();
+        }
         window.present();
     });
 
     app.run();
 }
 
+/// Carry out `--action` without opening a window, returning the exit code.
+fn run_action(action: &str, player: Option<String>) -> i32 {
+    let result = cli_action::Action::parse(action).and_then(|action| {
+        let config = config::Config::load();
+        let client = mpris_client::MprisClient::new();
+        client.set_player_priority(config.player_priority);
+        if let Some(name) = player {
+            // Falling back to another player would send a scripted command
+            // to the wrong one
+            let running = mpris_client::MprisClient::get_available_players()
+                .iter()
+                .any(|p| p.identity.to_lowercase() == name.to_lowercase());
+            if !running {
+                return Err(format!("Player {} not found", name));
+            }
+            client.set_preferred_player(Some(name));
+        }
+        cli_action::run(&client, action)
+    });
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
 fn load_css() {
     let provider = gtk::CssProvider::new();
     provider.load_from_string(
@@ -31,6 +149,33 @@ fn load_css() {
             box-shadow: 0 4px 12px rgba(0, 0, 0, 0.12),
                         0 8px 24px rgba(0, 0, 0, 0.06);
             background-color: @shade_color;
+            transition: filter 250ms ease-in-out;
+        }
+
+        /* Optional blurred art behind the window */
+        .art-backdrop {
+            opacity: 0.35;
+        }
+
+        /* Optional grayscale art while paused */
+        .desaturate-when-paused.paused .album-art {
+            filter: grayscale(100%);
+        }
+
+        /* Controls overlaid on the art, over a scrim so they stay readable */
+        .art-controls {
+            padding: 24px 8px 8px 8px;
+            border-radius: 0 0 24px 24px;
+            background-image: linear-gradient(to top, rgba(0, 0, 0, 0.6), rgba(0, 0, 0, 0));
+            color: white;
+        }
+
+        /* Controls that follow the pointer over the art */
+        .art-controls-floating {
+            padding: 4px 8px;
+            border-radius: 999px;
+            background-color: rgba(0, 0, 0, 0.6);
+            color: white;
         }
 
         /* Album art placeholder - bold initial on shaded background */
@@ -42,8 +187,6 @@ fn load_css() {
             border-radius: 24px;
             box-shadow: 0 4px 12px rgba(0, 0, 0, 0.12),
                         0 8px 24px rgba(0, 0, 0, 0.06);
-            min-width: 180px;
-            min-height: 180px;
         }
 
         /* Title styling - using Libadwaita heading styles */
@@ -167,3 +310,78 @@ fn load_css() {
         gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
     );
 }
+
+/// Larger text and controls with stronger contrast, for low-vision users.
+const ACCESSIBLE_CSS: &str = r#"
+    .title-1 {
+        font-size: 2rem;
+    }
+
+    .title-3 {
+        font-size: 1.35rem;
+        font-weight: 700;
+    }
+
+    .caption {
+        font-size: 1.1rem;
+    }
+
+    .dim-label {
+        opacity: 1;
+    }
+
+    progressringbutton button {
+        min-width: 68px;
+        min-height: 68px;
+    }
+
+    .circular.flat {
+        min-width: 56px;
+        min-height: 56px;
+        border: 2px solid @window_fg_color;
+    }
+
+    /* Keep the paused button solid rather than translucent */
+    progressringbutton button.play-pause.paused {
+        background-color: @accent_bg_color;
+        color: @accent_fg_color;
+    }
+
+    button:focus-visible {
+        outline: 3px solid @accent_color;
+        outline-offset: 2px;
+    }
+
+    scale trough {
+        min-height: 8px;
+    }
+"#;
+
+/// CSS that depends on the config, layered over the built-in styles.
+fn load_config_css(config: &config::Config) {
+    let mut css = String::new();
+
+    if let Some(ref color) = config.art_background {
+        // Round-trip through RGBA so only a valid color reaches the CSS
+        match gtk::gdk::RGBA::parse(color.as_str()) {
+            Ok(rgba) => css.push_str(&format!(".album-art {{ background-color: {}; }}\n", rgba)),
+            Err(_) => eprintln!("Ignoring invalid art_background color: {}", color),
+        }
+    }
+
+    if config.accessible_mode {
+        css.push_str(ACCESSIBLE_CSS);
+    }
+
+    if css.is_empty() {
+        return;
+    }
+
+    let provider = gtk::CssProvider::new();
+    provider.load_from_string(&css);
+    gtk::style_context_add_provider_for_display(
+        &gtk::gdk::Display::default().expect("Could not connect to a display."),
+        &provider,
+        gtk::STYLE_PROVIDER_PRIORITY_APPLICATION + 1,
+    );
+}
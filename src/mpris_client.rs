@@ -1,20 +1,114 @@
-use mpris::{PlaybackStatus, Player, PlayerFinder};
-use std::sync::mpsc::{channel, Receiver, Sender};
+use dbus::arg::{ArgType, PropMap, Variant};
+use dbus::blocking::stdintf::org_freedesktop_dbus::Properties;
+use dbus::blocking::Connection;
+use dbus::message::MatchRule;
+use mpris::{FindingError, LoopStatus, Metadata, PlaybackStatus, Player, PlayerFinder, TrackID};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-#[derive(Clone, Debug, Default)]
+use crate::listen_log::ListenLog;
+use crate::status_file::StatusFile;
+
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct MediaInfo {
     pub title: String,
     pub artist: String,
     pub album: String,
+    /// `xesam:trackNumber`, when the player gives a positive one.
+    pub track_number: Option<u32>,
+    /// `xesam:discNumber`, when the player gives a positive one.
+    pub disc_number: Option<u32>,
+    pub comment: Option<String>,
+    /// `mpris:trackid`, needed to set an absolute position; see
+    /// `MprisClient::set_track_position`.
+    pub track_id: Option<String>,
+    /// `xesam:url`, the location of the track itself.
+    pub url: Option<String>,
+    pub player_identity: String,
+    /// The player's `.desktop` file name without the extension, if it says.
+    pub desktop_entry: Option<String>,
     pub art_url: Option<String>,
     pub status: PlayerStatus,
     pub position: Option<Duration>,
+    pub buffered: Option<Duration>,
     pub length: Option<Duration>,
     pub volume: Option<f64>,
     pub can_control: bool,
+    pub can_seek: bool,
+    pub can_play: bool,
+    pub can_pause: bool,
+    pub can_go_next: bool,
+    pub can_go_previous: bool,
+    /// `xesam:userRating`, from 0.0 to 1.0. `None` when the player doesn't
+    /// rate tracks.
+    pub rating: Option<f64>,
+    /// Whether `MprisClient::set_rating` works for this player.
+    pub can_rate: bool,
+    /// Playback speed, 1.0 being normal. `None` when the player doesn't
+    /// expose it.
+    pub rate: Option<f64>,
+    /// Lowest and highest rate the player accepts. `None` when the rate is
+    /// fixed.
+    pub rate_range: Option<(f64, f64)>,
+    /// `None` when the player doesn't expose shuffle.
+    pub shuffle: Option<bool>,
+    /// `None` when the player doesn't expose a loop status.
+    pub loop_mode: Option<LoopMode>,
+    /// Player-specific properties from `EXTRA_PROPERTIES` that the player
+    /// exposes, as (label, value) pairs.
+    pub extra_properties: Vec<(&'static str, String)>,
+    /// ReplayGain and loudness values from `LOUDNESS_METADATA_KEYS` that the
+    /// track's metadata carries, as (label, value) pairs.
+    pub loudness: Vec<(&'static str, String)>,
+    /// Whether the player has a TrackList, whether or not it was read.
+    pub has_track_list: bool,
+    /// Total length of the tracks after this one in the player's
+    /// TrackList. `None` when the player has no track list, or it wasn't
+    /// read; see `MprisClient::set_fetch_queue`.
+    pub queued_after: Option<Duration>,
+    /// The tracks after this one in the player's TrackList, in order.
+    /// `None` when the player has no track list, or it wasn't read.
+    pub queue: Option<Vec<QueuedTrack>>,
+    /// Whether playback will stop when this track ends; see
+    /// `MprisClient::set_stop_after_current`.
+    pub stop_after_current: bool,
+    /// Set while the monitor is (re)establishing its bus connection.
+    pub reconnecting: bool,
+    /// Player selection this was read under; see
+    /// `MprisClient::selection_generation`.
+    pub selection_generation: usize,
+}
+
+impl MediaInfo {
+    /// Whether the two differ in nothing but the position and buffered
+    /// amount, which change on every read while playing.
+    pub fn same_except_progress(&self, other: &MediaInfo) -> bool {
+        let with_other_progress = MediaInfo {
+            position: other.position,
+            buffered: other.buffered,
+            ..self.clone()
+        };
+        with_other_progress == *other
+    }
+
+    /// This info as it should stand `elapsed` after it was read, for moving
+    /// the position along between updates.
+    pub fn advanced_by(&self, elapsed: Duration) -> MediaInfo {
+        let mut info = self.clone();
+        if info.status == PlayerStatus::Playing {
+            let rate = info.rate.unwrap_or(1.0).max(0.0);
+            let length = info.length.filter(|length| !length.is_zero());
+            info.position = info.position.map(|position| {
+                let position = position + elapsed.mul_f64(rate);
+                length.map_or(position, |length| position.min(length))
+            });
+        }
+        info
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -35,66 +129,889 @@ impl From<PlaybackStatus> for PlayerStatus {
     }
 }
 
+/// Mirrors the MPRIS `LoopStatus`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoopMode {
+    None,
+    Track,
+    Playlist,
+}
+
+impl LoopMode {
+    /// The mode the repeat button steps to: off, whole playlist, current
+    /// track, then off again.
+    pub fn next(self) -> Self {
+        match self {
+            LoopMode::None => LoopMode::Playlist,
+            LoopMode::Playlist => LoopMode::Track,
+            LoopMode::Track => LoopMode::None,
+        }
+    }
+}
+
+impl From<LoopStatus> for LoopMode {
+    fn from(status: LoopStatus) -> Self {
+        match status {
+            LoopStatus::None => LoopMode::None,
+            LoopStatus::Track => LoopMode::Track,
+            LoopStatus::Playlist => LoopMode::Playlist,
+        }
+    }
+}
+
+impl From<LoopMode> for LoopStatus {
+    fn from(mode: LoopMode) -> Self {
+        match mode {
+            LoopMode::None => LoopStatus::None,
+            LoopMode::Track => LoopStatus::Track,
+            LoopMode::Playlist => LoopStatus::Playlist,
+        }
+    }
+}
+
+/// Label used for the gapless playback property, so the UI can give it an
+/// indicator of its own.
+pub const GAPLESS_LABEL: &str = "Gapless";
+
+/// Properties some players add to the Player interface beyond the MPRIS
+/// spec, with the label to show them under. Players that don't have them
+/// are unaffected; add entries here as useful ones turn up.
+const EXTRA_PROPERTIES: &[(&str, &str)] = &[
+    ("Gapless", GAPLESS_LABEL),
+    ("GaplessPlayback", GAPLESS_LABEL),
+    ("Crossfade", "Crossfade"),
+    ("CrossfadeDuration", "Crossfade"),
+    ("ReplayGainMode", "ReplayGain"),
+];
+
+/// Metadata keys that may carry how far a stream has buffered, in
+/// microseconds from the start of the track. There is no standard property
+/// for it; add the vendor keys players actually use here as they turn up.
+const BUFFERED_METADATA_KEYS: &[&str] = &["xesam:buffered", "mpris:buffered"];
+
+/// Metadata keys some players pass through from the file's tags, with the
+/// label to show them under. None of them are in the xesam spec, so
+/// different players use different names for the same tag.
+const LOUDNESS_METADATA_KEYS: &[(&str, &str)] = &[
+    ("xesam:replayGainTrackGain", "Track Gain"),
+    ("replaygain_track_gain", "Track Gain"),
+    ("xesam:replayGainAlbumGain", "Album Gain"),
+    ("replaygain_album_gain", "Album Gain"),
+    ("xesam:replayGainTrackPeak", "Track Peak"),
+    ("replaygain_track_peak", "Track Peak"),
+    ("xesam:loudness", "Loudness"),
+    ("r128_track_gain", "R128 Track Gain"),
+];
+
+/// The loudness values in `metadata`, one per label. Gains given as bare
+/// numbers are shown in dB; strings (usually already "-6.20 dB") are shown
+/// as they are.
+fn loudness_from_metadata(metadata: &Metadata) -> Vec<(&'static str, String)> {
+    let mut loudness: Vec<(&'static str, String)> = Vec::new();
+    for (key, label) in LOUDNESS_METADATA_KEYS {
+        if loudness.iter().any(|(existing, _)| existing == label) {
+            continue;
+        }
+        let Some(value) = metadata.get(key) else {
+            continue;
+        };
+        let value = match (value.as_str(), value.as_f64()) {
+            (Some(text), _) => text.trim().to_string(),
+            (None, Some(number)) => format_loudness(label, number),
+            (None, None) => continue,
+        };
+        if !value.is_empty() {
+            loudness.push((label, value));
+        }
+    }
+    loudness
+}
+
+fn format_loudness(label: &str, value: f64) -> String {
+    if label.ends_with("Peak") {
+        format!("{:.6}", value)
+    } else if label == "Loudness" {
+        format!("{:.1} LUFS", value)
+    } else {
+        format!("{:+.2} dB", value)
+    }
+}
+
+const ART_URL_KEY: &str = "mpris:artUrl";
+
+/// The first of `keys` whose metadata value looks like usable art. Values
+/// can be URLs, paths, `data:` URIs or bare base64 image data, which is
+/// turned into a `data:` URI.
+fn resolve_art_url(metadata: &Metadata, keys: &[String]) -> Option<String> {
+    keys.iter()
+        .filter_map(|key| metadata.get(key))
+        .filter_map(|value| {
+            value
+                .as_str()
+                .or_else(|| value.as_str_array().and_then(|v| v.first().copied()))
+        })
+        .find_map(art_candidate)
+}
+
+fn art_candidate(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+
+    if value.starts_with("http://") || value.starts_with("https://") {
+        return Some(value.to_string());
+    }
+    if let Some(data) = value.strip_prefix("data:") {
+        // Only base64 payloads are handled when loading
+        return data.contains(";base64,").then(|| value.to_string());
+    }
+    if let Some(path) = art_local_path(value) {
+        return std::path::Path::new(&path)
+            .exists()
+            .then(|| value.to_string());
+    }
+
+    // Anything long and made only of base64 characters is taken as raw
+    // image data; real images are never this short
+    let is_base64 = value.len() >= 64
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'=' | b'\n' | b'\r'));
+    is_base64.then(|| format!("data:;base64,{}", value))
+}
+
+/// `url` without its query string or fragment, for telling which file it
+/// names. Fetching should still use the full URL, since signed CDN links
+/// carry their credentials in the query.
+pub fn strip_query_and_fragment(url: &str) -> &str {
+    let end = url.find(['?', '#']).unwrap_or(url.len());
+    &url[..end]
+}
+
+/// The file a `file://` art URL or plain path points to, percent-decoded,
+/// or `None` for anything else.
+pub fn art_local_path(url: &str) -> Option<String> {
+    let path = match url.strip_prefix("file://") {
+        Some(path) => strip_query_and_fragment(path),
+        // A bare path is taken literally; '?' and '#' are valid in file names
+        None if url.starts_with('/') => url,
+        None => return None,
+    };
+    urlencoding::decode(path).map(|p| p.into_owned()).ok()
+}
+
+/// Like `run_command`, but pausing and resuming ramp the volume down or up
+/// over `fade` first. Any other command arriving meanwhile cancels the
+/// ramp and is handed back to be run next, except another play or pause,
+/// which only cancels it. Players without a volume pause and resume
+/// immediately.
+fn run_command_with_fade(
+    p: &Player,
+    cmd: Command,
+    fade: Duration,
+    commands: &Receiver<Command>,
+) -> (Result<(), CommandError>, Option<Command>) {
+    let playing = matches!(p.get_playback_status(), Ok(PlaybackStatus::Playing));
+    let pausing = match cmd {
+        Command::Pause => playing,
+        Command::PlayPause => playing,
+        Command::Play => false,
+        _ => return (run_command(p, cmd), None),
+    };
+    let Ok(volume) = p.get_volume() else {
+        return (run_command(p, cmd), None);
+    };
+
+    // A second press of play/pause means "never mind", not a new toggle
+    let ramp = |from, to| {
+        ramp_volume(p, from, to, fade, commands).map(|cmd| match cmd {
+            Command::PlayPause | Command::Play | Command::Pause => None,
+            cmd => Some(cmd),
+        })
+    };
+
+    if pausing {
+        let interrupted_by = ramp(volume, 0.0);
+        // Cancelled: leave it playing, at the volume it had
+        let result = match interrupted_by {
+            Some(_) => Ok(()),
+            None => run_command(p, cmd),
+        };
+        let _ = p.set_volume(volume);
+        (result, interrupted_by.flatten())
+    } else {
+        let _ = p.set_volume(0.0);
+        let result = run_command(p, cmd);
+        if result.is_err() {
+            let _ = p.set_volume(volume);
+            return (result, None);
+        }
+        let interrupted_by = ramp(0.0, volume);
+        if interrupted_by.is_some() {
+            let _ = p.set_volume(volume);
+        }
+        (result, interrupted_by.flatten())
+    }
+}
+
+/// Step the volume from `from` to `to` over `duration`. Stops early and
+/// returns the command if one arrives.
+fn ramp_volume(
+    p: &Player,
+    from: f64,
+    to: f64,
+    duration: Duration,
+    commands: &Receiver<Command>,
+) -> Option<Command> {
+    let steps = (duration.as_millis() / FADE_STEP.as_millis()).max(1) as u32;
+    for step in 1..=steps {
+        if let Ok(cmd) = commands.try_recv() {
+            return Some(cmd);
+        }
+        thread::sleep(duration / steps);
+        let _ = p.set_volume(from + (to - from) * step as f64 / steps as f64);
+    }
+    None
+}
+
+/// The player to act on: the one the user picked, else the running player
+/// that comes first in `priority`, else whichever one MPRIS says is active.
+fn find_player(
+    finder: &PlayerFinder,
+    preferred: Option<&str>,
+    priority: &[String],
+) -> Result<Player, FindingError> {
+    if let Some(name) = preferred {
+        if let Ok(player) = finder.find_by_name(name) {
+            return Ok(player);
+        }
+    }
+
+    if !priority.is_empty() {
+        match finder.find_all() {
+            Ok(mut players) => {
+                let identities: Vec<&str> = players.iter().map(|p| p.identity()).collect();
+                if let Some(index) = highest_priority(&identities, priority) {
+                    return Ok(players.swap_remove(index));
+                }
+            }
+            Err(e @ FindingError::DBusError(_)) => return Err(e),
+            Err(_) => {}
+        }
+    }
+
+    finder.find_active()
+}
+
+/// `find_player` with the current preference and priority list.
+fn find_wanted_player(
+    finder: &PlayerFinder,
+    preferred_player: &Mutex<Option<String>>,
+    player_priority: &Mutex<Vec<String>>,
+) -> Result<Player, FindingError> {
+    let preferred_name = preferred_player.lock().ok().and_then(|pref| pref.clone());
+    let priority = player_priority
+        .lock()
+        .map(|p| p.clone())
+        .unwrap_or_default();
+    find_player(finder, preferred_name.as_deref(), &priority)
+}
+
+/// Bring the status file and listen log, where set, up to date with `info`.
+fn record_progress(
+    status_file: &Mutex<Option<StatusFile>>,
+    listen_log: &Mutex<Option<ListenLog>>,
+    info: &MediaInfo,
+) {
+    if let Ok(mut status_file) = status_file.lock() {
+        if let Some(ref mut status_file) = *status_file {
+            status_file.update(info);
+        }
+    }
+    if let Ok(mut listen_log) = listen_log.lock() {
+        if let Some(ref mut listen_log) = *listen_log {
+            listen_log.update(info);
+        }
+    }
+}
+
+/// Whether `info` is worth sending after `last_sent`. The position moves on
+/// every read while playing and the UI advances it by itself, so it only
+/// counts when it's strayed from where the last update said it would be,
+/// as after a seek.
+fn is_news(last_sent: Option<&(MediaInfo, Instant)>, info: &MediaInfo) -> bool {
+    let Some((last, sent_at)) = last_sent else {
+        return true;
+    };
+    if !last.same_except_progress(info) || last.buffered != info.buffered {
+        return true;
+    }
+    let expected = last.advanced_by(sent_at.elapsed()).position;
+    match (expected, info.position) {
+        (Some(expected), Some(position)) => expected.abs_diff(position) > POSITION_TOLERANCE,
+        (expected, position) => expected != position,
+    }
+}
+
+/// Index of the identity listed first in `priority`, ignoring case.
+fn highest_priority(identities: &[&str], priority: &[String]) -> Option<usize> {
+    priority.iter().find_map(|wanted| {
+        identities
+            .iter()
+            .position(|identity| identity.eq_ignore_ascii_case(wanted.trim()))
+    })
+}
+
+/// Consecutive D-Bus errors from player lookups before the monitor
+/// rebuilds its connection.
+const RECONNECT_AFTER_FAILURES: u32 = 3;
+/// How often the monitor looks for players appearing or going away. Changes
+/// to the player being shown arrive as events instead.
+const PLAYER_SCAN_INTERVAL: Duration = Duration::from_secs(2);
+/// How often the status file and listen log are brought up to date with
+/// the position while playing, between updates from the player.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+/// How far a read position may stray from the one the last update implies
+/// before it's sent again.
+const POSITION_TOLERANCE: Duration = Duration::from_secs(1);
+/// How long a player's watcher waits for a signal before checking whether
+/// it has been dropped.
+const WATCHER_CANCEL_CHECK: Duration = Duration::from_millis(500);
+/// How long to wait before retrying a connection that couldn't be made.
+const RECONNECT_RETRY_DELAY: Duration = Duration::from_secs(5);
+/// How long a player can go missing before the UI is told it's gone, so a
+/// momentary D-Bus hiccup doesn't flash the empty state.
+const PLAYER_LOSS_GRACE: Duration = Duration::from_millis(1500);
+/// How soon to look again for a player that went missing.
+const PLAYER_LOSS_RETRY: Duration = Duration::from_millis(250);
+
+/// How often the all-players view is refreshed while it's shown.
+const ALL_PLAYERS_POLL: Duration = Duration::from_secs(1);
+
+/// Time between volume steps while fading on pause/resume.
+const FADE_STEP: Duration = Duration::from_millis(40);
+
+/// How close to the end of the track "stop after this track" stops
+/// playback. The monitor reads the player again just before then, so this
+/// catches the end before the next track starts.
+const STOP_AFTER_MARGIN: Duration = Duration::from_secs(1);
+
+/// Tracks shorter than this may have their length in the wrong unit.
+const MIN_PLAUSIBLE_LENGTH: Duration = Duration::from_secs(1);
+/// How far the position may run past a reinterpreted length before the
+/// next larger unit is tried.
+const LENGTH_TOLERANCE: Duration = Duration::from_secs(2);
+
+static LOGGED_LENGTH_CORRECTION: Mutex<Option<String>> = Mutex::new(None);
+
+/// `mpris:length` is microseconds per the spec, but some players send
+/// milliseconds or seconds. Only a length too short to be a track is
+/// reinterpreted, and not when the position is still inside it, since then
+/// it's a genuinely short clip. The position alone never scales a length
+/// up: right after a track change it can still belong to the previous,
+/// longer track. Returns the length and, if it was reinterpreted, the unit
+/// used.
+fn normalize_length(raw: u64, position: Option<Duration>) -> (Duration, Option<&'static str>) {
+    const UNITS: [(u64, &str); 2] = [(1_000, "ms"), (1_000_000, "s")];
+
+    let length = Duration::from_micros(raw);
+    let suspect = length < MIN_PLAUSIBLE_LENGTH && position.is_none_or(|p| p > length);
+    if !suspect {
+        return (length, None);
+    }
+
+    for (scale, unit) in UNITS {
+        let Some(micros) = raw.checked_mul(scale) else {
+            break;
+        };
+        let scaled = Duration::from_micros(micros);
+        let plausible = scaled >= MIN_PLAUSIBLE_LENGTH
+            && position.is_none_or(|p| p <= scaled + LENGTH_TOLERANCE);
+        if plausible {
+            return (scaled, Some(unit));
+        }
+    }
+    (length, None)
+}
+
+/// Why a command wasn't carried out.
+#[derive(Clone, Debug)]
+pub enum CommandError {
+    /// No player was found to send the command to.
+    NoPlayer(&'static str),
+    /// The player doesn't advertise support for the action (e.g. `CanSeek`
+    /// is false), so the control should be disabled.
+    Unsupported(&'static str),
+    /// The player supports the action but the call errored.
+    Failed(&'static str, String),
+}
+
+/// The outcome of one command, reported back to the UI for every command
+/// sent, in the order they were sent.
+#[derive(Clone, Debug)]
+pub struct CommandResult {
+    /// Which command this is the outcome of. Messages are worded from the
+    /// action in the `CommandError`.
+    pub kind: CommandKind,
+    pub result: Result<(), CommandError>,
+}
+
+/// Which command a `CommandResult` is for, without its arguments.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandKind {
+    PlayPause,
+    Play,
+    Pause,
+    Stop,
+    Next,
+    Previous,
+    Seek,
+    SetPosition,
+    SetVolume,
+    ToggleMute,
+    PauseAll,
+    PlayAll,
+    Raise,
+    QuitPlayer,
+    SetRate,
+    GoTo,
+    SetShuffle,
+    SetLoop,
+    SetRating,
+}
+
+/// A track waiting in the player's TrackList.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueuedTrack {
+    /// The track's id in the list, for `MprisClient::go_to`.
+    pub id: String,
+    pub title: String,
+    pub artist: String,
+    pub length: Option<Duration>,
+}
+
+/// A running player, as listed in the player menu.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AvailablePlayer {
+    pub identity: String,
+    /// Its `.desktop` file name without the extension, if it says.
+    pub desktop_entry: Option<String>,
+}
+
 enum Command {
     PlayPause,
+    Play,
+    Pause,
+    Stop,
     Next,
     Previous,
     Seek(i64),
+    /// With no track id, the player's current track is looked up first.
+    SetPosition(Option<String>, Duration),
     SetVolume(f64),
+    ToggleMute,
+    PauseAll,
+    PlayAll,
+    Raise,
+    QuitPlayer,
+    SetRate(f64),
+    GoTo(String),
+    SetShuffle(bool),
+    SetLoop(LoopMode),
+    /// Rate the current track, from 0.0 to 1.0.
+    SetRating(f64),
+    /// Run the command on the player with this bus name instead of the
+    /// selected one.
+    OnPlayer(String, Box<Command>),
+}
+
+impl Command {
+    fn kind(&self) -> CommandKind {
+        match self {
+            Command::PlayPause => CommandKind::PlayPause,
+            Command::Play => CommandKind::Play,
+            Command::Pause => CommandKind::Pause,
+            Command::Stop => CommandKind::Stop,
+            Command::Next => CommandKind::Next,
+            Command::Previous => CommandKind::Previous,
+            Command::Seek(_) => CommandKind::Seek,
+            Command::SetPosition(..) => CommandKind::SetPosition,
+            Command::SetVolume(_) => CommandKind::SetVolume,
+            Command::ToggleMute => CommandKind::ToggleMute,
+            Command::PauseAll => CommandKind::PauseAll,
+            Command::PlayAll => CommandKind::PlayAll,
+            Command::Raise => CommandKind::Raise,
+            Command::QuitPlayer => CommandKind::QuitPlayer,
+            Command::SetRate(_) => CommandKind::SetRate,
+            Command::GoTo(_) => CommandKind::GoTo,
+            Command::SetShuffle(_) => CommandKind::SetShuffle,
+            Command::SetLoop(_) => CommandKind::SetLoop,
+            Command::SetRating(_) => CommandKind::SetRating,
+            Command::OnPlayer(_, cmd) => cmd.kind(),
+        }
+    }
+
+    /// Describes the action for messages like "Couldn't {action}".
+    fn action(&self) -> &'static str {
+        match self {
+            Command::PlayPause => "play or pause",
+            Command::Play => "play",
+            Command::Pause => "pause",
+            Command::Stop => "stop",
+            Command::Next => "skip to the next track",
+            Command::Previous => "go to the previous track",
+            Command::Seek(_) => "seek",
+            Command::SetPosition(..) => "set position",
+            Command::SetVolume(_) => "set the volume",
+            Command::ToggleMute => "mute or unmute",
+            Command::PauseAll => "pause all players",
+            Command::PlayAll => "resume all players",
+            Command::Raise => "raise",
+            Command::QuitPlayer => "quit the player",
+            Command::SetRate(_) => "change the playback speed",
+            Command::GoTo(_) => "jump to the track",
+            Command::SetShuffle(_) => "change shuffle",
+            Command::SetLoop(_) => "change repeat",
+            Command::SetRating(_) => "rate the track",
+            Command::OnPlayer(_, cmd) => cmd.action(),
+        }
+    }
+}
+
+/// Carry out `cmd` on the selected player. `PauseAll` and `PlayAll` are
+/// handled by the command thread itself, since they aren't tied to one
+/// player.
+fn run_command(p: &Player, cmd: Command) -> Result<(), CommandError> {
+    let action = cmd.action();
+    let result = match cmd {
+        // Not every player implements PlayPause; fall back to Play or Pause
+        // based on the current status
+        Command::PlayPause => p.play_pause().or_else(|_| match p.get_playback_status() {
+            Ok(PlaybackStatus::Playing) => p.pause(),
+            _ => p.play(),
+        }),
+        Command::Play => p.play(),
+        Command::Pause => p.pause(),
+        Command::Stop => {
+            if let Ok(false) = p.can_stop() {
+                return Err(CommandError::Unsupported(action));
+            }
+            p.stop()
+        }
+        Command::Next => p.next(),
+        Command::Previous => p.previous(),
+        Command::Seek(offset) => {
+            if let Ok(false) = p.can_seek() {
+                return Err(CommandError::Unsupported("seek"));
+            }
+            p.seek(offset)
+        }
+        Command::SetPosition(track_id, position) => {
+            if let Ok(false) = p.can_seek() {
+                return Err(CommandError::Unsupported("seek"));
+            }
+            // SetPosition is ignored unless it names the current track
+            let track_id = match track_id {
+                Some(id) => TrackID::new(id).ok(),
+                None => p.get_metadata().ok().and_then(|m| m.track_id()),
+            };
+            let Some(track_id) = track_id else {
+                return Err(CommandError::Unsupported(action));
+            };
+            p.set_position(track_id, &position)
+        }
+        Command::SetVolume(v) => {
+            if let Ok(false) = p.can_control() {
+                return Err(CommandError::Unsupported(action));
+            }
+            p.set_volume(v.clamp(0.0, 1.0))
+        }
+        Command::SetRate(rate) => {
+            let Ok(range) = p.get_valid_playback_rate_range() else {
+                return Err(CommandError::Unsupported(action));
+            };
+            if range.start >= range.end {
+                return Err(CommandError::Unsupported(action));
+            }
+            p.set_playback_rate(rate.clamp(range.start, range.end))
+        }
+        Command::SetShuffle(shuffle) => p.set_shuffle(shuffle),
+        Command::SetLoop(mode) => p.set_loop_status(mode.into()),
+        Command::Raise => {
+            if let Ok(false) = p.can_raise() {
+                return Err(CommandError::Unsupported(action));
+            }
+            p.raise()
+        }
+        Command::QuitPlayer => {
+            if let Ok(false) = p.can_quit() {
+                return Err(CommandError::Unsupported(action));
+            }
+            p.quit()
+        }
+        Command::SetRating(rating) => return set_rating(p, rating),
+        Command::GoTo(id) => match TrackID::new(id) {
+            Ok(id) => p.go_to(&id),
+            Err(e) => return Err(CommandError::Failed(action, e)),
+        },
+        Command::PauseAll | Command::PlayAll | Command::ToggleMute | Command::OnPlayer(..) => {
+            Ok(())
+        }
+    };
+    result.map_err(|e| CommandError::Failed(action, e.to_string()))
+}
+
+/// Whether `set_rating` knows how to rate tracks in `p`.
+fn can_rate(p: &Player) -> bool {
+    p.bus_name_player_name_part() == "rhythmbox"
+}
+
+/// Rate the track playing in `p`. MPRIS metadata is read-only, so this
+/// goes through the player's own D-Bus interface, which only Rhythmbox
+/// (rating from 0 to 5 stars, by track URI) is known to offer.
+fn set_rating(p: &Player, rating: f64) -> Result<(), CommandError> {
+    let action = Command::SetRating(rating).action();
+    if !can_rate(p) {
+        return Err(CommandError::Unsupported(action));
+    }
+    let Some(uri) = p
+        .get_metadata()
+        .ok()
+        .and_then(|m| m.url().map(str::to_string))
+    else {
+        return Err(CommandError::Unsupported(action));
+    };
+
+    let mut properties = PropMap::new();
+    properties.insert(
+        "rating".to_string(),
+        Variant(Box::new(rating.clamp(0.0, 1.0) * 5.0)),
+    );
+    let result = Connection::new_session().and_then(|connection| {
+        connection
+            .with_proxy(
+                "org.gnome.Rhythmbox3",
+                "/org/gnome/Rhythmbox3/RhythmDB",
+                Duration::from_millis(500),
+            )
+            .method_call(
+                "org.gnome.Rhythmbox3.RhythmDB",
+                "SetEntryProperties",
+                (uri, properties),
+            )
+    });
+    result.map_err(|e| CommandError::Failed(action, e.to_string()))
+}
+
+/// Mute `p`, saving its volume in `muted_volume`, or if it's already at
+/// zero, restore the saved volume. A player muted some other way stays
+/// muted, as there's no level to go back to.
+fn toggle_mute(p: &Player, muted_volume: &Mutex<Option<f64>>) -> Result<(), CommandError> {
+    let action = Command::ToggleMute.action();
+    if let Ok(false) = p.can_control() {
+        return Err(CommandError::Unsupported(action));
+    }
+    let Ok(volume) = p.get_volume() else {
+        return Err(CommandError::Unsupported(action));
+    };
+    let Ok(mut muted_volume) = muted_volume.lock() else {
+        return Ok(());
+    };
+    let result = if volume > 0.0 {
+        *muted_volume = Some(volume);
+        p.set_volume(0.0)
+    } else if let Some(volume) = muted_volume.take() {
+        p.set_volume(volume)
+    } else {
+        Ok(())
+    };
+    result.map_err(|e| CommandError::Failed(action, e.to_string()))
 }
 
 #[derive(Clone)]
 pub struct MprisClient {
     command_sender: Sender<Command>,
     preferred_player: Arc<Mutex<Option<String>>>,
+    // Last volume the user set for each player identity, restored when
+    // switching back to a player that reset its level in the meantime.
+    player_volumes: Arc<Mutex<HashMap<String, f64>>>,
+    // Volume from before `toggle_mute` muted the player, to go back to
+    muted_volume: Arc<Mutex<Option<f64>>>,
+    default_volume: Arc<Mutex<Option<f64>>>,
+    // Metadata keys to look for cover art in, in order
+    art_keys: Arc<Mutex<Vec<String>>>,
+    // Player identities to prefer in Auto mode, highest first
+    player_priority: Arc<Mutex<Vec<String>>>,
+    status_file: Arc<Mutex<Option<StatusFile>>>,
+    listen_log: Arc<Mutex<Option<ListenLog>>>,
+    // How long to ramp the volume over when pausing or resuming
+    fade_duration: Arc<Mutex<Duration>>,
+    stop_after_current: Arc<AtomicBool>,
+    // Whether the monitor reads the TrackList, which costs a metadata call
+    // for the queued tracks whenever they change
+    fetch_queue: Arc<AtomicBool>,
+    // Bumped by `reconnect()`; each thread rebuilds its bus connection
+    // when it sees a new value.
+    reconnect_generation: Arc<AtomicUsize>,
+    // Bumped on every player selection change, and stamped on each
+    // MediaInfo so updates read from the previous player can be dropped.
+    selection_generation: Arc<AtomicUsize>,
     monitor_tick: Sender<()>,
     monitor_tick_receiver: Arc<Mutex<Option<Receiver<()>>>>,
+    command_result_receiver: Arc<Mutex<Option<Receiver<CommandResult>>>>,
 }
 
 impl MprisClient {
     pub fn new() -> Self {
         let (command_sender, command_receiver) = channel::<Command>();
         let preferred_player: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+        let player_volumes: Arc<Mutex<HashMap<String, f64>>> = Arc::new(Mutex::new(HashMap::new()));
+        let muted_volume: Arc<Mutex<Option<f64>>> = Arc::new(Mutex::new(None));
+        let default_volume: Arc<Mutex<Option<f64>>> = Arc::new(Mutex::new(None));
+        let art_keys = Arc::new(Mutex::new(vec![ART_URL_KEY.to_string()]));
+        let player_priority: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+        let fade_duration = Arc::new(Mutex::new(Duration::ZERO));
+        let reconnect_generation = Arc::new(AtomicUsize::new(0));
+        let selection_generation = Arc::new(AtomicUsize::new(0));
         let (monitor_tick, tick_receiver) = channel::<()>();
         let monitor_tick_receiver = Arc::new(Mutex::new(Some(tick_receiver)));
+        let (command_result_sender, result_receiver) = channel::<CommandResult>();
+        let command_result_receiver = Arc::new(Mutex::new(Some(result_receiver)));
 
         let preferred_player_clone = preferred_player.clone();
+        let player_priority_clone = player_priority.clone();
+        let fade_duration_clone = fade_duration.clone();
+        let player_volumes_clone = player_volumes.clone();
+        let muted_volume_clone = muted_volume.clone();
+        let reconnect_generation_clone = reconnect_generation.clone();
+        let command_tick = monitor_tick.clone();
 
         // Spawn a thread that owns the Player and handles commands
         thread::spawn(move || {
             let mut player: Option<Player> = None;
-            let finder = match PlayerFinder::new() {
-                Ok(f) => f,
-                Err(_) => return,
-            };
+            let mut finder: Option<PlayerFinder> = None;
+            let mut seen_generation = reconnect_generation_clone.load(Ordering::SeqCst);
+            // A command that arrived during a volume fade and cut it short
+            let mut pending: Option<Command> = None;
 
             loop {
-                let Ok(cmd) = command_receiver.recv() else {
-                    break;
+                let cmd = match pending.take() {
+                    Some(cmd) => cmd,
+                    None => match command_receiver.recv() {
+                        Ok(cmd) => cmd,
+                        Err(_) => break,
+                    },
+                };
+                let kind = cmd.kind();
+                let action = cmd.action();
+                let report = |result: Result<(), CommandError>| {
+                    // Refresh right away so the UI reflects the change
+                    if result.is_ok() {
+                        let _ = command_tick.send(());
+                    }
+                    let _ = command_result_sender.send(CommandResult { kind, result });
                 };
 
+                // Start over with a fresh bus connection after a manual
+                // reconnect, or if the last one couldn't be made
+                let generation = reconnect_generation_clone.load(Ordering::SeqCst);
+                if generation != seen_generation {
+                    finder = None;
+                    seen_generation = generation;
+                }
+                if finder.is_none() {
+                    match PlayerFinder::new() {
+                        Ok(new_finder) => finder = Some(new_finder),
+                        Err(e) => {
+                            report(Err(CommandError::Failed(action, e.to_string())));
+                            continue;
+                        }
+                    }
+                }
+                let Some(ref active_finder) = finder else {
+                    continue;
+                };
+
+                // These act on every player, regardless of the selection
+                if let Command::PauseAll | Command::PlayAll = cmd {
+                    let players = match active_finder.find_all() {
+                        Ok(players) if !players.is_empty() => players,
+                        _ => {
+                            report(Err(CommandError::NoPlayer(action)));
+                            continue;
+                        }
+                    };
+                    let mut result = Ok(());
+                    for p in &players {
+                        let status = p.get_playback_status().ok();
+                        let outcome = match (&cmd, status) {
+                            (Command::PauseAll, Some(PlaybackStatus::Playing)) => p.pause(),
+                            (Command::PlayAll, Some(PlaybackStatus::Paused)) => p.play(),
+                            _ => Ok(()),
+                        };
+                        // Keep going so one broken player doesn't stop the rest
+                        if let Err(e) = outcome {
+                            result = Err(CommandError::Failed(action, e.to_string()));
+                        }
+                    }
+                    report(result);
+                    continue;
+                }
+
+                if let Command::OnPlayer(bus_name, cmd) = cmd {
+                    let target = active_finder
+                        .find_all()
+                        .ok()
+                        .and_then(|players| players.into_iter().find(|p| p.bus_name() == bus_name));
+                    match target {
+                        Some(p) => report(run_command(&p, *cmd)),
+                        None => report(Err(CommandError::NoPlayer(action))),
+                    }
+                    continue;
+                }
+
                 let preferred_name = preferred_player_clone
                     .lock()
                     .ok()
                     .and_then(|pref| pref.clone());
+                let priority = player_priority_clone
+                    .lock()
+                    .map(|p| p.clone())
+                    .unwrap_or_default();
 
-                player = if let Some(ref preferred) = preferred_name {
-                    finder
-                        .find_by_name(preferred)
-                        .ok()
-                        .or_else(|| finder.find_active().ok())
-                } else {
-                    finder.find_active().ok()
-                };
+                let found = find_player(active_finder, preferred_name.as_deref(), &priority);
+                // A bus error (as opposed to no player) means the connection
+                // may be stale; rebuild it for the next command
+                if let Err(FindingError::DBusError(_)) = found {
+                    finder = None;
+                }
+                player = found.ok();
 
-                if let Some(ref p) = player {
-                    let _ = match cmd {
-                        Command::PlayPause => p.play_pause(),
-                        Command::Next => p.next(),
-                        Command::Previous => p.previous(),
-                        Command::Seek(offset) => p.seek(offset),
-                        Command::SetVolume(v) => p.set_volume(v.max(0.0)),
-                    };
+                let Some(ref p) = player else {
+                    report(Err(CommandError::NoPlayer(action)));
+                    continue;
+                };
+                if let Command::SetVolume(v) = cmd {
+                    if let Ok(mut volumes) = player_volumes_clone.lock() {
+                        volumes.insert(p.identity().to_string(), v.clamp(0.0, 1.0));
+                    }
+                }
+                if let Command::ToggleMute = cmd {
+                    report(toggle_mute(p, &muted_volume_clone));
+                    continue;
+                }
+                let fade = fade_duration_clone
+                    .lock()
+                    .map(|d| *d)
+                    .unwrap_or(Duration::ZERO);
+                if fade.is_zero() {
+                    report(run_command(p, cmd));
+                } else {
+                    let (result, interrupted_by) =
+                        run_command_with_fade(p, cmd, fade, &command_receiver);
+                    report(result);
+                    pending = interrupted_by;
                 }
             }
         });
@@ -102,15 +1019,99 @@ impl MprisClient {
         Self {
             command_sender,
             preferred_player,
+            player_volumes,
+            muted_volume,
+            default_volume,
+            art_keys,
+            player_priority,
+            status_file: Arc::new(Mutex::new(None)),
+            listen_log: Arc::new(Mutex::new(None)),
+            fade_duration,
+            stop_after_current: Arc::new(AtomicBool::new(false)),
+            fetch_queue: Arc::new(AtomicBool::new(false)),
+            reconnect_generation,
+            selection_generation,
             monitor_tick,
             monitor_tick_receiver,
+            command_result_receiver,
         }
     }
 
     pub fn set_preferred_player(&self, player_name: Option<String>) {
         *self.preferred_player.lock().unwrap() = player_name;
+        self.selection_generation.fetch_add(1, Ordering::SeqCst);
         // Wake the monitor thread so it picks up the new player immediately
-        // instead of waiting for the next 500ms tick.
+        // instead of at the next player scan.
+        let _ = self.monitor_tick.send(());
+    }
+
+    /// Volume to apply once when a player is first seen. Must be set
+    /// before `start_monitoring` to affect the player that's already running.
+    pub fn set_default_volume(&self, volume: Option<f64>) {
+        *self.default_volume.lock().unwrap() = volume.map(|v| v.clamp(0.0, 1.0));
+    }
+
+    /// Player identities (e.g. "Spotify") to pick in Auto mode, in order of
+    /// preference, before falling back to whichever player is active.
+    pub fn set_player_priority(&self, priority: Vec<String>) {
+        *self.player_priority.lock().unwrap() = priority;
+        let _ = self.monitor_tick.send(());
+    }
+
+    /// Keep `status_file` up to date with every update, or stop writing one.
+    pub fn set_status_file(&self, status_file: Option<StatusFile>) {
+        *self.status_file.lock().unwrap() = status_file;
+    }
+
+    /// Record listens to `listen_log` as tracks play, or stop recording.
+    pub fn set_listen_log(&self, listen_log: Option<ListenLog>) {
+        *self.listen_log.lock().unwrap() = listen_log;
+    }
+
+    /// Metadata keys to take cover art from, tried in order. An empty list
+    /// means just `mpris:artUrl`.
+    pub fn set_art_metadata_keys(&self, keys: Vec<String>) {
+        let keys = if keys.is_empty() {
+            vec![ART_URL_KEY.to_string()]
+        } else {
+            keys
+        };
+        *self.art_keys.lock().unwrap() = keys;
+    }
+
+    /// Current player selection generation. A `MediaInfo` stamped with an
+    /// older value describes a player that is no longer selected.
+    pub fn selection_generation(&self) -> usize {
+        self.selection_generation.load(Ordering::SeqCst)
+    }
+
+    /// Fade the volume out before pausing and back in after resuming, over
+    /// `duration`. Zero pauses and resumes immediately.
+    pub fn set_pause_fade(&self, duration: Duration) {
+        *self.fade_duration.lock().unwrap() = duration;
+    }
+
+    /// Stop playback when the current track ends. MPRIS has no standard
+    /// property for this and none of the common players expose their own,
+    /// so it's done here by stopping the player near the end of the track.
+    /// Disarms itself once it fires or the player changes.
+    pub fn set_stop_after_current(&self, enabled: bool) {
+        self.stop_after_current.store(enabled, Ordering::SeqCst);
+        let _ = self.monitor_tick.send(());
+    }
+
+    /// Read the player's TrackList on every update, for the queue and the
+    /// time left in the playlist. Off by default, since nothing else needs it.
+    pub fn set_fetch_queue(&self, enabled: bool) {
+        if self.fetch_queue.swap(enabled, Ordering::SeqCst) != enabled && enabled {
+            let _ = self.monitor_tick.send(());
+        }
+    }
+
+    /// Drop and rebuild the D-Bus connections used for monitoring and
+    /// commands, for when updates have stopped arriving.
+    pub fn reconnect(&self) {
+        self.reconnect_generation.fetch_add(1, Ordering::SeqCst);
         let _ = self.monitor_tick.send(());
     }
 
@@ -120,12 +1121,21 @@ impl MprisClient {
         self.monitor_tick_receiver.lock().unwrap().take()
     }
 
-    pub fn get_available_players() -> Vec<String> {
+    /// Take the receiver for the outcome of each command, successful or
+    /// not. Must be called exactly once.
+    pub fn take_command_results(&self) -> Option<Receiver<CommandResult>> {
+        self.command_result_receiver.lock().unwrap().take()
+    }
+
+    pub fn get_available_players() -> Vec<AvailablePlayer> {
         if let Ok(finder) = PlayerFinder::new() {
             if let Ok(players) = finder.find_all() {
                 players
                     .into_iter()
-                    .map(|p| p.identity().to_string())
+                    .map(|p| AvailablePlayer {
+                        identity: p.identity().to_string(),
+                        desktop_entry: p.get_desktop_entry().ok().flatten(),
+                    })
                     .collect()
             } else {
                 vec![]
@@ -142,79 +1152,494 @@ impl MprisClient {
     pub fn start_monitoring(&self, tick_receiver: Receiver<()>) -> Receiver<MediaInfo> {
         let (info_sender, info_receiver) = channel();
         let preferred_player = self.preferred_player.clone();
+        let player_volumes = self.player_volumes.clone();
+        let default_volume = self.default_volume.clone();
+        let reconnect_generation = self.reconnect_generation.clone();
+        let selection_generation = self.selection_generation.clone();
+        let art_keys = self.art_keys.clone();
+        let player_priority = self.player_priority.clone();
+        let status_file = self.status_file.clone();
+        let listen_log = self.listen_log.clone();
+        let stop_after_current = self.stop_after_current.clone();
+        let fetch_queue = self.fetch_queue.clone();
 
         thread::spawn(move || {
-            let finder = match PlayerFinder::new() {
-                Ok(f) => f,
-                Err(_) => {
-                    let _ = info_sender.send(MediaInfo::default());
-                    return;
-                }
-            };
+            let mut finder: Option<PlayerFinder> = None;
+            let mut seen_generation = reconnect_generation.load(Ordering::SeqCst);
+            let mut bus_failures = 0;
+            let mut connected_once = false;
 
             let mut last_status = PlayerStatus::Stopped;
             let mut last_title = String::new();
+            let mut last_identity: Option<String> = None;
+            let mut last_position: Option<Duration> = None;
+            let mut seen_players: HashSet<String> = HashSet::new();
+            let mut watcher: Option<PlayerWatcher> = None;
+            let mut properties_connection = None;
+            let mut queue_cache: Option<QueueCache> = None;
+            // Last info sent and when, so refreshes that find nothing new
+            // send nothing
+            let mut last_sent: Option<(MediaInfo, Instant)> = None;
+            // When the player last shown stopped being found
+            let mut lost_since: Option<Instant> = None;
+            // Title of the track that was playing when stop-after was armed
+            let mut armed_title: Option<String> = None;
+
+            // Player events and ticks from the UI both wake the monitor
+            let (wake_sender, wake_receiver) = channel();
+            thread::spawn({
+                let wake_sender = wake_sender.clone();
+                move || {
+                    for () in tick_receiver {
+                        if wake_sender.send(()).is_err() {
+                            break;
+                        }
+                    }
+                }
+            });
 
             loop {
-                let preferred_name = preferred_player
-                    .lock()
-                    .ok()
-                    .and_then(|pref| pref.clone());
+                // Rebuild every bus connection on a manual reconnect or after
+                // repeated bus errors, which usually means the session bus
+                // went away under us (suspend/resume, bus restart)
+                let generation = reconnect_generation.load(Ordering::SeqCst);
+                if generation != seen_generation || bus_failures >= RECONNECT_AFTER_FAILURES {
+                    finder = None;
+                    seen_generation = generation;
+                    bus_failures = 0;
+                }
 
-                let player_opt = if let Some(ref preferred) = preferred_name {
-                    finder
-                        .find_by_name(preferred)
-                        .ok()
-                        .or_else(|| finder.find_active().ok())
-                } else {
-                    finder.find_active().ok()
+                if finder.is_none() {
+                    // Nothing to report on the very first connection
+                    if connected_once {
+                        let reconnecting = MediaInfo {
+                            reconnecting: true,
+                            selection_generation: selection_generation.load(Ordering::SeqCst),
+                            ..Default::default()
+                        };
+                        if info_sender.send(reconnecting).is_err() {
+                            break;
+                        }
+                        last_sent = None;
+                    }
+
+                    finder = PlayerFinder::new().ok();
+                    connected_once |= finder.is_some();
+                    watcher = None;
+                    properties_connection = Connection::new_session().ok();
+                    last_identity = None;
+                    last_position = None;
+
+                    if finder.is_none() {
+                        if wake_receiver.recv_timeout(RECONNECT_RETRY_DELAY).is_ok() {
+                            while wake_receiver.try_recv().is_ok() {}
+                        }
+                        continue;
+                    }
+                }
+                let Some(ref active_finder) = finder else {
+                    continue;
                 };
 
+                // Read before the preference so a switch that lands mid-read
+                // marks this update as stale
+                let generation = selection_generation.load(Ordering::SeqCst);
+                let found = find_wanted_player(active_finder, &preferred_player, &player_priority);
+
+                if let Err(FindingError::DBusError(ref e)) = found {
+                    eprintln!("D-Bus error while looking for players: {}", e);
+                    bus_failures += 1;
+                } else {
+                    bus_failures = 0;
+                }
+                let player_opt = found.ok();
+
+                // Only set while stop-after has a track end to wait for
+                let mut stop_check_at: Option<Instant> = None;
+
                 let info = if let Some(player) = player_opt {
-                    Self::get_media_info(&player)
+                    lost_since = None;
+                    if watcher
+                        .as_ref()
+                        .is_none_or(|w| w.unique_name != player.unique_name() || w.is_finished())
+                    {
+                        watcher = Some(PlayerWatcher::spawn(&player, wake_sender.clone()));
+                    }
+                    let identity = player.identity().to_string();
+                    let keys = art_keys.lock().map(|k| k.clone()).unwrap_or_default();
+                    // Only carry the status over while it's the same player
+                    let known_status = (last_identity.as_deref() == Some(identity.as_str()))
+                        .then_some(&last_status);
+                    let mut info = Self::get_media_info(&player, &keys, known_status);
+                    if let Some(ref connection) = properties_connection {
+                        info.extra_properties =
+                            Self::get_extra_properties(connection, player.unique_name());
+                    }
+                    info.has_track_list = player.supports_track_lists();
+                    if info.has_track_list && fetch_queue.load(Ordering::SeqCst) {
+                        info.queue = Self::upcoming_tracks(&player, &mut queue_cache);
+                        info.queued_after = info
+                            .queue
+                            .as_ref()
+                            .map(|tracks| tracks.iter().filter_map(|track| track.length).sum());
+                    }
+
+                    if last_identity.as_deref() != Some(identity.as_str()) {
+                        if seen_players.insert(identity.clone()) {
+                            let volume = default_volume.lock().ok().and_then(|v| *v);
+                            Self::apply_default_volume(&player, volume, &mut info);
+                        }
+                        Self::restore_player_volume(&player, &player_volumes, &mut info);
+                        last_identity = Some(identity);
+                        last_position = None;
+                        // Armed for the previous player's track, not this one's
+                        stop_after_current.store(false, Ordering::SeqCst);
+                    }
+
+                    if let Some(ref watcher) = watcher {
+                        if info.title != last_title {
+                            watcher.forget();
+                        }
+
+                        // A position that doesn't move while playing is stale;
+                        // trust the last Seeked report, interpolated, instead.
+                        let reported = info.position;
+                        let playing = info.status == PlayerStatus::Playing;
+                        let stale = playing && reported.is_some() && reported == last_position;
+                        if let Some(position) = watcher.position(playing) {
+                            if stale || reported.is_none() {
+                                info.position = Some(position);
+                            }
+                        }
+                        last_position = reported;
+                    }
+
+                    if stop_after_current.load(Ordering::SeqCst) {
+                        let armed = armed_title.get_or_insert_with(|| info.title.clone());
+                        let near_end = match (info.position, info.length) {
+                            (Some(position), Some(length)) if !length.is_zero() => {
+                                length.saturating_sub(position) <= STOP_AFTER_MARGIN
+                            }
+                            _ => false,
+                        };
+                        // In case the end was missed, e.g. after a seek
+                        let track_changed = *armed != info.title;
+                        let playing = info.status == PlayerStatus::Playing;
+                        if playing
+                            && (near_end || track_changed)
+                            && player.stop().or_else(|_| player.pause()).is_ok()
+                        {
+                            stop_after_current.store(false, Ordering::SeqCst);
+                            info.status = PlayerStatus::Stopped;
+                        } else if let (true, Some(position), Some(length)) =
+                            (playing, info.position, info.length)
+                        {
+                            // Nothing is sent as the track nears its end, so
+                            // come back then to stop it
+                            let remaining = length.saturating_sub(position + STOP_AFTER_MARGIN);
+                            let rate = info.rate.filter(|rate| *rate > 0.0).unwrap_or(1.0);
+                            stop_check_at = Some(Instant::now() + remaining.div_f64(rate));
+                        }
+                    } else {
+                        armed_title = None;
+                    }
+                    info.stop_after_current = stop_after_current.load(Ordering::SeqCst);
+
+                    info
                 } else {
+                    // Hold on to what's shown for a moment in case the player
+                    // comes straight back, unless the user just picked
+                    // another player and is waiting to see it
+                    let lost_at = *lost_since.get_or_insert_with(Instant::now);
+                    let holding = last_sent.as_ref().is_some_and(|(info, _)| {
+                        !info.player_identity.is_empty() && info.selection_generation == generation
+                    });
+                    if holding && lost_at.elapsed() < PLAYER_LOSS_GRACE {
+                        if wake_receiver.recv_timeout(PLAYER_LOSS_RETRY).is_ok() {
+                            while wake_receiver.try_recv().is_ok() {}
+                        }
+                        continue;
+                    }
+
+                    watcher = None;
+                    last_identity = None;
+                    last_position = None;
                     MediaInfo::default()
                 };
 
+                let mut info = info;
+                info.selection_generation = generation;
+
+                record_progress(&status_file, &listen_log, &info);
+
                 let status = info.status.clone();
                 let title = info.title.clone();
+                let read = (info.clone(), Instant::now());
 
-                if info_sender.send(info).is_err() {
-                    break;
+                if is_news(last_sent.as_ref(), &info) {
+                    last_sent = Some((info.clone(), Instant::now()));
+                    if info_sender.send(info).is_err() {
+                        break;
+                    }
                 }
 
-                let poll_interval = match status {
-                    PlayerStatus::Playing => Duration::from_millis(500),
-                    PlayerStatus::Paused => Duration::from_secs(3),
-                    PlayerStatus::Stopped => Duration::from_secs(5),
-                };
-
-                let status_changed = status != last_status || title != last_title;
                 last_status = status;
                 last_title = title;
 
-                let timeout = if status_changed {
-                    Duration::from_millis(500)
+                // Sleep until the player has something to say or the UI asks
+                // for a refresh. Every so often check whether some other
+                // player should be shown instead, since appearing players
+                // don't send anything to the watcher.
+                let shown = watcher.as_ref().map(|w| w.unique_name.clone());
+                let playing = read.0.status == PlayerStatus::Playing;
+                let mut scanned_at = Instant::now();
+                loop {
+                    let slice = if playing {
+                        PROGRESS_INTERVAL
+                    } else {
+                        PLAYER_SCAN_INTERVAL
+                    };
+                    let timeout = stop_check_at.map_or(slice, |at| {
+                        at.saturating_duration_since(Instant::now()).min(slice)
+                    });
+                    if wake_receiver.recv_timeout(timeout).is_ok() {
+                        while wake_receiver.try_recv().is_ok() {}
+                        break;
+                    }
+                    if stop_check_at.is_some_and(|at| Instant::now() >= at) {
+                        break;
+                    }
+                    if playing {
+                        let (ref info, read_at) = read;
+                        record_progress(
+                            &status_file,
+                            &listen_log,
+                            &info.advanced_by(read_at.elapsed()),
+                        );
+                    }
+                    if scanned_at.elapsed() >= PLAYER_SCAN_INTERVAL {
+                        scanned_at = Instant::now();
+                        let wanted =
+                            find_wanted_player(active_finder, &preferred_player, &player_priority);
+                        if wanted.ok().map(|p| p.unique_name().to_string()) != shown {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+
+        info_receiver
+    }
+
+    /// Poll every running player while the all-players view is shown, as
+    /// last reported on `shown`. Updates list them by bus name in the order
+    /// they're found, and leave out the position since the cards don't show
+    /// it. The thread sleeps on `shown` while the view is hidden and exits
+    /// once either end of the channels is dropped.
+    pub fn start_monitoring_all(
+        &self,
+        shown: Receiver<bool>,
+    ) -> Receiver<Vec<(String, MediaInfo)>> {
+        let (players_sender, players_receiver) = channel();
+        let art_keys = self.art_keys.clone();
+
+        thread::spawn(move || {
+            let mut finder: Option<PlayerFinder> = None;
+            let mut last_sent: Option<Vec<(String, MediaInfo)>> = None;
+            let mut visible = false;
+
+            loop {
+                let change = if visible {
+                    shown.recv_timeout(ALL_PLAYERS_POLL)
                 } else {
-                    poll_interval
+                    // Nothing to poll for until the view comes back
+                    shown.recv().map_err(|_| RecvTimeoutError::Disconnected)
+                };
+                match change {
+                    Ok(now_visible) => visible = now_visible,
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+                if !visible {
+                    continue;
+                }
+                if finder.is_none() {
+                    finder = PlayerFinder::new().ok();
+                }
+                let Some(ref active_finder) = finder else {
+                    continue;
+                };
+                let players = match active_finder.find_all() {
+                    Ok(players) => players,
+                    Err(_) => {
+                        // Most likely a stale connection; start over next time
+                        finder = None;
+                        continue;
+                    }
                 };
 
-                if tick_receiver.recv_timeout(timeout).is_ok() {
-                    while tick_receiver.try_recv().is_ok() {}
+                let keys = art_keys.lock().map(|k| k.clone()).unwrap_or_default();
+                let infos: Vec<(String, MediaInfo)> = players
+                    .iter()
+                    .map(|player| {
+                        let info = MediaInfo {
+                            position: None,
+                            buffered: None,
+                            ..Self::get_media_info(player, &keys, None)
+                        };
+                        (player.bus_name().to_string(), info)
+                    })
+                    .collect();
+                if last_sent.as_ref() != Some(&infos) {
+                    last_sent = Some(infos.clone());
+                    if players_sender.send(infos).is_err() {
+                        break;
+                    }
                 }
             }
         });
 
-        info_receiver
+        players_receiver
     }
 
-    fn get_media_info(player: &Player) -> MediaInfo {
-        let metadata = player.get_metadata().ok();
-        let status = player
-            .get_playback_status()
+    /// The first time a player is seen, set it to the configured default
+    /// volume if it exposes one.
+    fn apply_default_volume(player: &Player, volume: Option<f64>, info: &mut MediaInfo) {
+        let (Some(volume), Some(_)) = (volume, info.volume) else {
+            return;
+        };
+        if player.set_volume(volume).is_ok() {
+            info.volume = Some(volume);
+        }
+    }
+
+    /// On switching to a player, put back the volume the user last chose
+    /// for it if the player has since changed it.
+    fn restore_player_volume(
+        player: &Player,
+        player_volumes: &Mutex<HashMap<String, f64>>,
+        info: &mut MediaInfo,
+    ) {
+        let Some(current) = info.volume else {
+            return;
+        };
+        let saved = player_volumes
+            .lock()
             .ok()
-            .map(PlayerStatus::from)
-            .unwrap_or_default();
+            .and_then(|volumes| volumes.get(player.identity()).copied());
+
+        if let Some(saved) = saved {
+            if (saved - current).abs() > 0.005 && player.set_volume(saved).is_ok() {
+                info.volume = Some(saved);
+            }
+        }
+    }
+
+    /// The tracks after the current one, for players with a TrackList.
+    fn upcoming_tracks(
+        player: &Player,
+        cache: &mut Option<QueueCache>,
+    ) -> Option<Vec<QueuedTrack>> {
+        let Some(track_list) = player.checked_get_track_list().ok().flatten() else {
+            *cache = None;
+            return None;
+        };
+        let current = player.get_metadata().ok()?.track_id()?;
+        let track_ids = track_list.ids();
+
+        if let Some(ref cached) = cache {
+            if cached.current == current && cached.track_ids == track_ids {
+                return cached.upcoming.clone();
+            }
+        }
+
+        let upcoming = track_ids
+            .iter()
+            .position(|id| *id == current)
+            .and_then(|index| match &track_ids[index + 1..] {
+                [] => Some(Vec::new()),
+                later => player.get_tracks_metadata(later).ok().map(|tracks| {
+                    later
+                        .iter()
+                        .zip(tracks)
+                        .map(|(id, m)| QueuedTrack {
+                            id: id.to_string(),
+                            title: m.title().unwrap_or_default().to_string(),
+                            artist: m.artists().map(|a| a.join(", ")).unwrap_or_default(),
+                            length: m
+                                .length_in_microseconds()
+                                .map(|raw| normalize_length(raw, None).0),
+                        })
+                        .collect()
+                }),
+            });
+        *cache = Some(QueueCache {
+            track_ids: track_ids.to_vec(),
+            current,
+            upcoming: upcoming.clone(),
+        });
+        upcoming
+    }
+
+    /// Read whichever `EXTRA_PROPERTIES` the player has. The mpris crate only
+    /// knows the standard ones, so this asks D-Bus for all of them directly.
+    fn get_extra_properties(
+        connection: &Connection,
+        unique_name: &str,
+    ) -> Vec<(&'static str, String)> {
+        let proxy = connection.with_proxy(
+            unique_name,
+            "/org/mpris/MediaPlayer2",
+            Duration::from_millis(500),
+        );
+        let Ok(properties) = proxy.get_all("org.mpris.MediaPlayer2.Player") else {
+            return Vec::new();
+        };
+
+        let mut extras: Vec<(&'static str, String)> = Vec::new();
+        for (name, label) in EXTRA_PROPERTIES {
+            if extras.iter().any(|(existing, _)| existing == label) {
+                continue;
+            }
+            let value = properties.get(*name).and_then(|variant| {
+                let value = &*variant.0;
+                match value.arg_type() {
+                    ArgType::Boolean => value
+                        .as_u64()
+                        .map(|v| if v != 0 { "On" } else { "Off" }.to_string()),
+                    ArgType::String => value.as_str().map(str::to_string),
+                    ArgType::Double => value.as_f64().map(|v| format!("{:.1}", v)),
+                    ArgType::Byte
+                    | ArgType::Int16
+                    | ArgType::UInt16
+                    | ArgType::Int32
+                    | ArgType::UInt32
+                    | ArgType::Int64 => value.as_i64().map(|v| v.to_string()),
+                    ArgType::UInt64 => value.as_u64().map(|v| v.to_string()),
+                    _ => None,
+                }
+            });
+            if let Some(value) = value {
+                extras.push((label, value));
+            }
+        }
+        extras
+    }
+
+    /// Read everything the UI shows from `player`. `known_status` is the
+    /// status from the previous poll of the same player, kept when the
+    /// status can't be read so a transient error doesn't flash "stopped".
+    fn get_media_info(
+        player: &Player,
+        art_keys: &[String],
+        known_status: Option<&PlayerStatus>,
+    ) -> MediaInfo {
+        let metadata = player.get_metadata().ok();
+        let status = match player.get_playback_status() {
+            Ok(status) => PlayerStatus::from(status),
+            Err(_) => known_status.cloned().unwrap_or_default(),
+        };
 
         let (title, artist, album, art_url) = if let Some(ref m) = metadata {
             (
@@ -223,7 +1648,7 @@ impl MprisClient {
                     .and_then(|a| a.first().map(|s| s.to_string()))
                     .unwrap_or_else(|| "Unknown Artist".to_string()),
                 m.album_name().unwrap_or("").to_string(),
-                m.art_url().map(|s| s.to_string()),
+                resolve_art_url(m, art_keys),
             )
         } else {
             (
@@ -234,37 +1659,197 @@ impl MprisClient {
             )
         };
 
+        let track_number = metadata
+            .as_ref()
+            .and_then(|m| m.track_number())
+            .and_then(|n| u32::try_from(n).ok())
+            .filter(|&n| n > 0);
+        let disc_number = metadata
+            .as_ref()
+            .and_then(|m| m.disc_number())
+            .and_then(|n| u32::try_from(n).ok())
+            .filter(|&n| n > 0);
+
+        // xesam:comment is a list of strings per the spec, but some players
+        // send a single string. Radio players often put the station blurb here.
+        let comment = metadata
+            .as_ref()
+            .and_then(|m| m.get("xesam:comment"))
+            .and_then(|value| {
+                value
+                    .as_str_array()
+                    .map(|lines| lines.join("\n"))
+                    .or_else(|| value.as_str().map(str::to_string))
+            })
+            .map(|comment| comment.trim().to_string())
+            .filter(|comment| !comment.is_empty());
+
+        let rating = metadata
+            .as_ref()
+            .and_then(|m| m.get("xesam:userRating"))
+            .and_then(|value| value.as_f64())
+            .map(|rating| rating.clamp(0.0, 1.0));
+
         let position = player.get_position().ok();
+
+        let buffered = metadata
+            .as_ref()
+            .and_then(|m| {
+                BUFFERED_METADATA_KEYS
+                    .iter()
+                    .find_map(|key| m.get(key))
+                    .and_then(|value| {
+                        value
+                            .as_u64()
+                            .or_else(|| value.as_i64().and_then(|v| u64::try_from(v).ok()))
+                    })
+            })
+            .map(Duration::from_micros);
+
         let length = metadata
             .as_ref()
-            .and_then(|m| m.length())
-            .and_then(|l| Duration::try_from(l).ok());
+            .and_then(|m| m.length_in_microseconds())
+            .map(|raw| {
+                let (length, unit) = normalize_length(raw, position);
+                if let Some(unit) = unit {
+                    // Once per track, not on every poll
+                    let mut logged = LOGGED_LENGTH_CORRECTION.lock().unwrap();
+                    if logged.as_deref() != Some(title.as_str()) {
+                        eprintln!(
+                            "{} reported mpris:length {} for \"{}\"; treating it as {}",
+                            player.identity(),
+                            raw,
+                            title,
+                            unit
+                        );
+                        *logged = Some(title.clone());
+                    }
+                }
+                length
+            });
 
         let can_control = player.can_control().unwrap_or(false);
-        let volume = if can_control {
-            player.get_volume().ok()
+        let can_seek = can_control && player.can_seek().unwrap_or(false);
+        let can_play = can_control && player.can_play().unwrap_or(false);
+        let can_pause = can_control && player.can_pause().unwrap_or(false);
+        let can_go_next = can_control && player.can_go_next().unwrap_or(false);
+        let can_go_previous = can_control && player.can_go_previous().unwrap_or(false);
+        let (volume, shuffle, loop_mode) = if can_control {
+            (
+                player.get_volume().ok(),
+                player.checked_get_shuffle().ok().flatten(),
+                player
+                    .checked_get_loop_status()
+                    .ok()
+                    .flatten()
+                    .map(LoopMode::from),
+            )
+        } else {
+            (None, None, None)
+        };
+        let (rate, rate_range) = if can_control {
+            (
+                player.checked_get_playback_rate().ok().flatten(),
+                player
+                    .get_valid_playback_rate_range()
+                    .ok()
+                    .map(|range| (range.start, range.end))
+                    .filter(|(min, max)| min < max),
+            )
         } else {
-            None
+            (None, None)
         };
 
         MediaInfo {
             title,
             artist,
             album,
+            track_number,
+            disc_number,
+            comment,
+            track_id: metadata
+                .as_ref()
+                .and_then(|m| m.track_id())
+                .map(String::from),
+            url: metadata
+                .as_ref()
+                .and_then(|m| m.url())
+                .filter(|url| !url.is_empty())
+                .map(str::to_string),
+            player_identity: player.identity().to_string(),
+            desktop_entry: player.get_desktop_entry().ok().flatten(),
             art_url,
             status,
             position,
+            buffered,
             length,
             volume,
             can_control,
+            can_seek,
+            can_play,
+            can_pause,
+            can_go_next,
+            can_go_previous,
+            rating,
+            can_rate: can_control && can_rate(player),
+            rate,
+            rate_range,
+            shuffle,
+            loop_mode,
+            extra_properties: Vec::new(),
+            loudness: metadata
+                .as_ref()
+                .map(loudness_from_metadata)
+                .unwrap_or_default(),
+            has_track_list: false,
+            queued_after: None,
+            queue: None,
+            stop_after_current: false,
+            reconnecting: false,
+            selection_generation: 0,
         }
     }
 
+    /// Play or pause the player on `bus_name`, as listed by
+    /// `start_monitoring_all`, regardless of which player is selected.
+    pub fn play_pause_player(&self, bus_name: &str) -> anyhow::Result<()> {
+        self.send_to_player(bus_name, Command::PlayPause)
+    }
+
+    pub fn next_player(&self, bus_name: &str) -> anyhow::Result<()> {
+        self.send_to_player(bus_name, Command::Next)
+    }
+
+    pub fn previous_player(&self, bus_name: &str) -> anyhow::Result<()> {
+        self.send_to_player(bus_name, Command::Previous)
+    }
+
+    fn send_to_player(&self, bus_name: &str, cmd: Command) -> anyhow::Result<()> {
+        self.command_sender
+            .send(Command::OnPlayer(bus_name.to_string(), Box::new(cmd)))?;
+        Ok(())
+    }
+
     pub fn play_pause(&self) -> anyhow::Result<()> {
         self.command_sender.send(Command::PlayPause)?;
         Ok(())
     }
 
+    pub fn play(&self) -> anyhow::Result<()> {
+        self.command_sender.send(Command::Play)?;
+        Ok(())
+    }
+
+    pub fn pause(&self) -> anyhow::Result<()> {
+        self.command_sender.send(Command::Pause)?;
+        Ok(())
+    }
+
+    pub fn stop(&self) -> anyhow::Result<()> {
+        self.command_sender.send(Command::Stop)?;
+        Ok(())
+    }
+
     pub fn next(&self) -> anyhow::Result<()> {
         self.command_sender.send(Command::Next)?;
         Ok(())
@@ -280,8 +1865,455 @@ impl MprisClient {
         Ok(())
     }
 
+    /// Jump to an absolute position in the current track.
+    pub fn set_position(&self, position: Duration) -> anyhow::Result<()> {
+        self.command_sender
+            .send(Command::SetPosition(None, position))?;
+        Ok(())
+    }
+
+    /// Jump to an absolute position in the track with `track_id`, taken
+    /// from `MediaInfo::track_id`. Players ignore it once that track is no
+    /// longer current.
+    pub fn set_track_position(&self, track_id: &str, position: Duration) -> anyhow::Result<()> {
+        self.command_sender
+            .send(Command::SetPosition(Some(track_id.to_string()), position))?;
+        Ok(())
+    }
+
+    /// Set the player's volume, clamped to 0.0–1.0. Players that can't be
+    /// controlled refuse it with `CommandError::Unsupported`.
     pub fn set_volume(&self, volume: f64) -> anyhow::Result<()> {
-        self.command_sender.send(Command::SetVolume(volume))?;
+        // Turning it up by hand unmutes; there's nothing left to restore
+        if volume > 0.0 {
+            if let Ok(mut muted_volume) = self.muted_volume.lock() {
+                *muted_volume = None;
+            }
+        }
+        self.command_sender
+            .send(Command::SetVolume(volume.clamp(0.0, 1.0)))?;
+        Ok(())
+    }
+
+    /// Mute the player, or if it's muted, put back the volume it had
+    /// before. Players that can't be controlled refuse it with
+    /// `CommandError::Unsupported`.
+    pub fn toggle_mute(&self) -> anyhow::Result<()> {
+        self.command_sender.send(Command::ToggleMute)?;
+        Ok(())
+    }
+
+    /// Pause every player that is currently playing, not just the selected one.
+    pub fn pause_all(&self) -> anyhow::Result<()> {
+        self.command_sender.send(Command::PauseAll)?;
+        Ok(())
+    }
+
+    /// Resume every paused player.
+    pub fn play_all(&self) -> anyhow::Result<()> {
+        self.command_sender.send(Command::PlayAll)?;
+        Ok(())
+    }
+
+    pub fn set_shuffle(&self, shuffle: bool) -> anyhow::Result<()> {
+        self.command_sender.send(Command::SetShuffle(shuffle))?;
+        Ok(())
+    }
+
+    /// Set the playback speed, clamped to what the player accepts.
+    pub fn set_rate(&self, rate: f64) -> anyhow::Result<()> {
+        self.command_sender.send(Command::SetRate(rate))?;
+        Ok(())
+    }
+
+    /// Rate the current track, from 0.0 to 1.0, in players that allow it;
+    /// see `MediaInfo::can_rate`.
+    pub fn set_rating(&self, rating: f64) -> anyhow::Result<()> {
+        self.command_sender.send(Command::SetRating(rating))?;
         Ok(())
     }
+
+    pub fn set_loop(&self, mode: LoopMode) -> anyhow::Result<()> {
+        self.command_sender.send(Command::SetLoop(mode))?;
+        Ok(())
+    }
+
+    /// Start playing the queued track with `track_id`; see `QueuedTrack`.
+    pub fn go_to(&self, track_id: &str) -> anyhow::Result<()> {
+        self.command_sender
+            .send(Command::GoTo(track_id.to_string()))?;
+        Ok(())
+    }
+
+    /// Ask the player to quit. Its name then leaves the bus and the monitor
+    /// moves on to another player, or to showing nothing.
+    pub fn quit_player(&self) -> anyhow::Result<()> {
+        self.command_sender.send(Command::QuitPlayer)?;
+        Ok(())
+    }
+
+    /// Ask the player to bring its own window to the front.
+    pub fn raise(&self) -> anyhow::Result<()> {
+        self.command_sender.send(Command::Raise)?;
+        Ok(())
+    }
+}
+
+/// The last track list read by `upcoming_tracks`, kept so track
+/// metadata is only fetched again when the list or current track changes.
+struct QueueCache {
+    track_ids: Vec<TrackID>,
+    current: TrackID,
+    upcoming: Option<Vec<QueuedTrack>>,
+}
+
+/// Follows one player's signals on a thread of its own, waking the monitor
+/// whenever anything about the player changes.
+///
+/// Some players never update their `Position` property but do emit
+/// `Seeked`, so the last reported position is kept and advanced locally
+/// while playing. The thread has its own bus connection, which it closes
+/// when the player quits or within `WATCHER_CANCEL_CHECK` of the watcher
+/// being dropped.
+struct PlayerWatcher {
+    unique_name: String,
+    seeked: Arc<Mutex<Option<(Duration, Instant)>>>,
+    cancelled: Arc<AtomicBool>,
+    finished: Arc<AtomicBool>,
+}
+
+impl PlayerWatcher {
+    fn spawn(player: &Player, wake_sender: Sender<()>) -> Self {
+        let seeked: Arc<Mutex<Option<(Duration, Instant)>>> = Arc::default();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let finished = Arc::new(AtomicBool::new(false));
+        let unique_name = player.unique_name().to_string();
+        let bus_name = player.bus_name().to_string();
+
+        thread::spawn({
+            let unique_name = unique_name.clone();
+            let seeked = seeked.clone();
+            let cancelled = cancelled.clone();
+            let finished = finished.clone();
+            move || {
+                let quit = Arc::new(AtomicBool::new(false));
+                let connection =
+                    watch_signals(&unique_name, bus_name, seeked, wake_sender, quit.clone());
+                if let Some(connection) = connection {
+                    while !cancelled.load(Ordering::SeqCst) && !quit.load(Ordering::SeqCst) {
+                        // Left for the next refresh to pick up a new watcher
+                        if connection.process(WATCHER_CANCEL_CHECK).is_err() {
+                            break;
+                        }
+                    }
+                }
+                finished.store(true, Ordering::SeqCst);
+            }
+        });
+
+        Self {
+            unique_name,
+            seeked,
+            cancelled,
+            finished,
+        }
+    }
+
+    /// Whether the thread has stopped following the player.
+    fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::SeqCst)
+    }
+
+    /// Position implied by the player's last `Seeked` signal, advanced by the
+    /// time since if `playing`. Re-anchors at the result so time spent
+    /// paused between calls isn't counted.
+    fn position(&self, playing: bool) -> Option<Duration> {
+        let mut seeked = self.seeked.lock().ok()?;
+        let (position, at) = seeked.as_mut()?;
+        if playing {
+            *position += at.elapsed();
+        }
+        *at = Instant::now();
+        Some(*position)
+    }
+
+    /// Drop the last `Seeked` position, e.g. after a track change.
+    fn forget(&self) {
+        if let Ok(mut seeked) = self.seeked.lock() {
+            *seeked = None;
+        }
+    }
+}
+
+/// Open a bus connection that wakes the monitor on any signal from the
+/// player with `unique_name`, records its `Seeked` positions, and sets
+/// `quit` once `bus_name` goes away.
+fn watch_signals(
+    unique_name: &str,
+    bus_name: String,
+    seeked: Arc<Mutex<Option<(Duration, Instant)>>>,
+    wake_sender: Sender<()>,
+    quit: Arc<AtomicBool>,
+) -> Option<Connection> {
+    let connection = Connection::new_session().ok()?;
+
+    let rule = MatchRule::new_signal("org.mpris.MediaPlayer2.Player", "Seeked")
+        .with_path("/org/mpris/MediaPlayer2")
+        .with_sender(unique_name.to_string());
+    connection
+        .add_match(rule, {
+            let wake_sender = wake_sender.clone();
+            let quit = quit.clone();
+            move |(position,): (i64,), _, _| {
+                if let Ok(mut seeked) = seeked.lock() {
+                    let position = Duration::from_micros(position.max(0) as u64);
+                    *seeked = Some((position, Instant::now()));
+                }
+                if wake_sender.send(()).is_err() {
+                    quit.store(true, Ordering::SeqCst);
+                }
+                true
+            }
+        })
+        .ok()?;
+
+    let rule = MatchRule::new_signal("org.freedesktop.DBus.Properties", "PropertiesChanged")
+        .with_path("/org/mpris/MediaPlayer2")
+        .with_sender(unique_name.to_string());
+    connection
+        .add_match(rule, {
+            let quit = quit.clone();
+            move |(), _, _| {
+                if wake_sender.send(()).is_err() {
+                    quit.store(true, Ordering::SeqCst);
+                }
+                true
+            }
+        })
+        .ok()?;
+
+    let rule = MatchRule::new_signal("org.freedesktop.DBus", "NameOwnerChanged")
+        .with_sender("org.freedesktop.DBus");
+    connection
+        .add_match(
+            rule,
+            move |(name, _, new_owner): (String, String, String), _, _| {
+                if name == bus_name && new_owner.is_empty() {
+                    quit.store(true, Ordering::SeqCst);
+                }
+                true
+            },
+        )
+        .ok()?;
+
+    Some(connection)
+}
+
+impl Drop for PlayerWatcher {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spec_lengths_are_left_alone() {
+        let length = normalize_length(180_000_000, Some(Duration::from_secs(60)));
+        assert_eq!(length, (Duration::from_secs(180), None));
+    }
+
+    #[test]
+    fn millisecond_and_second_lengths_are_corrected() {
+        assert_eq!(
+            normalize_length(180_000, None),
+            (Duration::from_secs(180), Some("ms"))
+        );
+        assert_eq!(
+            normalize_length(180, None),
+            (Duration::from_secs(180), Some("s"))
+        );
+    }
+
+    #[test]
+    fn position_from_the_previous_track_leaves_the_length_alone() {
+        let length = normalize_length(180_000_000, Some(Duration::from_secs(600)));
+        assert_eq!(length, (Duration::from_secs(180), None));
+    }
+
+    #[test]
+    fn short_clips_are_kept_while_the_position_is_inside_them() {
+        let length = normalize_length(500_000, Some(Duration::from_millis(300)));
+        assert_eq!(length, (Duration::from_millis(500), None));
+    }
+
+    #[test]
+    fn position_picks_the_unit_for_a_suspect_length() {
+        // 1.2 s as milliseconds would already be over, so seconds
+        let length = normalize_length(1_200, Some(Duration::from_secs(100)));
+        assert_eq!(length, (Duration::from_secs(1200), Some("s")));
+    }
+
+    #[test]
+    fn art_candidates_accept_urls_and_data() {
+        let url = "https://example.com/cover.jpg";
+        assert_eq!(art_candidate(url), Some(url.to_string()));
+        let data_uri = "data:image/png;base64,iVBORw0KGgo=";
+        assert_eq!(art_candidate(data_uri), Some(data_uri.to_string()));
+        let raw = "iVBORw0KGgo".repeat(8);
+        assert_eq!(art_candidate(&raw), Some(format!("data:;base64,{}", raw)));
+    }
+
+    #[test]
+    fn art_candidates_reject_junk() {
+        assert_eq!(art_candidate(""), None);
+        assert_eq!(art_candidate("not a cover"), None);
+        assert_eq!(art_candidate("data:image/png,rawbytes"), None);
+        assert_eq!(art_candidate("file:///nonexistent/cover.jpg"), None);
+    }
+
+    #[test]
+    fn query_and_fragment_are_stripped() {
+        assert_eq!(
+            strip_query_and_fragment("https://cdn/x.jpg?sig=abc#frag"),
+            "https://cdn/x.jpg"
+        );
+        assert_eq!(
+            strip_query_and_fragment("https://cdn/x.jpg#frag"),
+            "https://cdn/x.jpg"
+        );
+        assert_eq!(
+            strip_query_and_fragment("https://cdn/x.jpg"),
+            "https://cdn/x.jpg"
+        );
+    }
+
+    #[test]
+    fn local_art_paths_are_decoded() {
+        assert_eq!(
+            art_local_path("file:///music/My%20Album/cover.jpg?size=large"),
+            Some("/music/My Album/cover.jpg".to_string())
+        );
+        assert_eq!(
+            art_local_path("/music/What?.jpg"),
+            Some("/music/What?.jpg".to_string())
+        );
+        assert_eq!(art_local_path("https://cdn/x.jpg"), None);
+    }
+
+    #[test]
+    fn progress_changes_are_not_display_changes() {
+        let info = MediaInfo {
+            title: "Song".to_string(),
+            position: Some(Duration::from_secs(1)),
+            ..Default::default()
+        };
+        let later = MediaInfo {
+            position: Some(Duration::from_secs(2)),
+            ..info.clone()
+        };
+        assert!(info.same_except_progress(&later));
+        let next_track = MediaInfo {
+            title: "Other".to_string(),
+            ..later
+        };
+        assert!(!info.same_except_progress(&next_track));
+    }
+
+    #[test]
+    fn only_seeks_make_position_news() {
+        let sent = MediaInfo {
+            status: PlayerStatus::Playing,
+            position: Some(Duration::from_secs(30)),
+            ..Default::default()
+        };
+        let last_sent = (sent.clone(), Instant::now());
+        let playing_on = MediaInfo {
+            position: Some(Duration::from_millis(30_400)),
+            ..sent.clone()
+        };
+        assert!(!is_news(Some(&last_sent), &playing_on));
+
+        let seeked = MediaInfo {
+            position: Some(Duration::from_secs(90)),
+            ..sent.clone()
+        };
+        assert!(is_news(Some(&last_sent), &seeked));
+
+        let paused = MediaInfo {
+            status: PlayerStatus::Paused,
+            ..playing_on
+        };
+        assert!(is_news(Some(&last_sent), &paused));
+        assert!(is_news(None, &sent));
+    }
+
+    #[test]
+    fn position_moves_on_only_while_playing() {
+        let info = MediaInfo {
+            status: PlayerStatus::Playing,
+            position: Some(Duration::from_secs(10)),
+            length: Some(Duration::from_secs(12)),
+            rate: Some(2.0),
+            ..Default::default()
+        };
+        let elapsed = Duration::from_millis(500);
+        assert_eq!(
+            info.advanced_by(elapsed).position,
+            Some(Duration::from_secs(11))
+        );
+        assert_eq!(
+            info.advanced_by(Duration::from_secs(5)).position,
+            Some(Duration::from_secs(12))
+        );
+
+        let paused = MediaInfo {
+            status: PlayerStatus::Paused,
+            ..info
+        };
+        assert_eq!(paused.advanced_by(elapsed).position, paused.position);
+    }
+
+    #[test]
+    fn priority_order_beats_running_order() {
+        let priority = vec!["Spotify".to_string(), "Firefox".to_string()];
+        assert_eq!(
+            highest_priority(&["Firefox", "spotify"], &priority),
+            Some(1)
+        );
+        assert_eq!(highest_priority(&["Firefox", "VLC"], &priority), Some(0));
+        assert_eq!(highest_priority(&["VLC"], &priority), None);
+    }
+
+    #[test]
+    fn numeric_loudness_gets_units() {
+        assert_eq!(format_loudness("Track Gain", -6.2), "-6.20 dB");
+        assert_eq!(format_loudness("Album Gain", 1.5), "+1.50 dB");
+        assert_eq!(format_loudness("Track Peak", 0.98), "0.980000");
+        assert_eq!(format_loudness("Loudness", -14.04), "-14.0 LUFS");
+    }
+
+    #[test]
+    fn zero_length_is_kept() {
+        assert_eq!(normalize_length(0, None), (Duration::ZERO, None));
+    }
+
+    #[test]
+    fn commands_on_another_player_report_their_own_kind() {
+        let cmd = Command::OnPlayer(
+            "org.mpris.MediaPlayer2.x".to_string(),
+            Box::new(Command::Seek(5)),
+        );
+        assert_eq!(cmd.kind(), CommandKind::Seek);
+        assert_eq!(cmd.action(), "seek");
+    }
+
+    #[test]
+    fn loop_mode_cycles_through_playlist_then_track() {
+        assert_eq!(LoopMode::None.next(), LoopMode::Playlist);
+        assert_eq!(LoopMode::Playlist.next(), LoopMode::Track);
+        assert_eq!(LoopMode::Track.next(), LoopMode::None);
+    }
 }
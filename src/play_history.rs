@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::mpris_client::{MediaInfo, PlayerStatus};
+use crate::util::write_atomically;
+
+/// Oldest tracks are dropped past this many.
+const MAX_TRACKS: usize = 100;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlayedTrack {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub player: String,
+    /// When it started playing, in seconds since the Unix epoch.
+    pub played_at: u64,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct HistoryFile {
+    tracks: Vec<PlayedTrack>,
+}
+
+/// Tracks that have played, most recent first, kept in memory and
+/// optionally in the cache directory so they survive restarts.
+#[derive(Default)]
+pub struct PlayHistory {
+    tracks: VecDeque<PlayedTrack>,
+    path: Option<PathBuf>,
+}
+
+impl PlayHistory {
+    fn path() -> PathBuf {
+        glib::user_cache_dir().join("empress").join("history.toml")
+    }
+
+    /// An empty history, or with `persist`, the one saved last time.
+    pub fn load(persist: bool) -> Self {
+        if !persist {
+            return Self::default();
+        }
+
+        let path = Self::path();
+        let file: HistoryFile = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| match toml::from_str(&contents) {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    eprintln!("Ignoring unreadable history {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        let mut tracks: VecDeque<PlayedTrack> = file.tracks.into();
+        tracks.truncate(MAX_TRACKS);
+        Self {
+            tracks,
+            path: Some(path),
+        }
+    }
+
+    pub fn tracks(&self) -> &VecDeque<PlayedTrack> {
+        &self.tracks
+    }
+
+    /// Add the track in `info` if it's playing and isn't the one already at
+    /// the top. Returns whether anything was added.
+    pub fn record(&mut self, info: &MediaInfo) -> bool {
+        if info.status != PlayerStatus::Playing || info.title.trim().is_empty() {
+            return false;
+        }
+        if let Some(last) = self.tracks.front() {
+            if last.title == info.title && last.artist == info.artist {
+                return false;
+            }
+        }
+
+        let played_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.tracks.push_front(PlayedTrack {
+            title: info.title.clone(),
+            artist: info.artist.clone(),
+            album: info.album.clone(),
+            player: info.player_identity.clone(),
+            played_at,
+        });
+        self.tracks.truncate(MAX_TRACKS);
+
+        if let Err(e) = self.save() {
+            eprintln!("Failed to save play history: {}", e);
+        }
+        true
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = HistoryFile {
+            tracks: self.tracks.iter().cloned().collect(),
+        };
+        write_atomically(path, toml::to_string(&file)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playing(title: &str) -> MediaInfo {
+        MediaInfo {
+            title: title.to_string(),
+            artist: "Artist".to_string(),
+            status: PlayerStatus::Playing,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn repeats_and_paused_tracks_are_not_recorded() {
+        let mut history = PlayHistory::default();
+        assert!(history.record(&playing("One")));
+        assert!(!history.record(&playing("One")));
+        assert!(!history.record(&MediaInfo {
+            status: PlayerStatus::Paused,
+            ..playing("Two")
+        }));
+        assert!(history.record(&playing("Two")));
+        assert_eq!(history.tracks().len(), 2);
+        assert_eq!(history.tracks()[0].title, "Two");
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut history = PlayHistory::default();
+        for i in 0..MAX_TRACKS + 5 {
+            history.record(&playing(&i.to_string()));
+        }
+        assert_eq!(history.tracks().len(), MAX_TRACKS);
+    }
+}
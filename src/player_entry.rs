@@ -0,0 +1,44 @@
+use gtk::glib;
+use gtk::subclass::prelude::*;
+use std::cell::RefCell;
+
+mod imp {
+    use super::*;
+
+    #[derive(Default)]
+    pub struct PlayerEntry {
+        pub identity: RefCell<String>,
+        pub icon_name: RefCell<String>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PlayerEntry {
+        const NAME: &'static str = "EmpressPlayerEntry";
+        type Type = super::PlayerEntry;
+    }
+
+    impl ObjectImpl for PlayerEntry {}
+}
+
+glib::wrapper! {
+    /// One row of the player menu: a player's name and the icon to show
+    /// beside it.
+    pub struct PlayerEntry(ObjectSubclass<imp::PlayerEntry>);
+}
+
+impl PlayerEntry {
+    pub fn new(identity: &str, icon_name: &str) -> Self {
+        let entry: Self = glib::Object::new();
+        entry.imp().identity.replace(identity.to_string());
+        entry.imp().icon_name.replace(icon_name.to_string());
+        entry
+    }
+
+    pub fn identity(&self) -> String {
+        self.imp().identity.borrow().clone()
+    }
+
+    pub fn icon_name(&self) -> String {
+        self.imp().icon_name.borrow().clone()
+    }
+}
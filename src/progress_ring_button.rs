@@ -1,7 +1,33 @@
+use gtk::glib::subclass::Signal;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
-use gtk::{cairo, glib, graphene};
-use std::cell::Cell;
+use gtk::{cairo, gdk, glib, graphene};
+use libadwaita as adw;
+use std::cell::{Cell, RefCell};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Clicks closer to the center than this fraction of the ring's radius
+/// play/pause as usual; further out they seek.
+const SEEK_DEAD_ZONE: f64 = 0.6;
+/// Gap between the ring and the edge of the widget.
+const RING_MARGIN: f64 = 4.0;
+/// Ring thickness unless `ring-width` says otherwise.
+const DEFAULT_RING_WIDTH: f64 = 3.0;
+/// How long the loading arc takes to go once round.
+const LOADING_TURN: Duration = Duration::from_secs(1);
+
+/// Where along the track a click at (`x`, `y`) points, going clockwise
+/// from the top of the ring, or `None` inside the dead zone.
+fn ring_fraction(x: f64, y: f64, width: f64, height: f64) -> Option<f64> {
+    let radius = width.min(height) / 2.0 - RING_MARGIN;
+    let (dx, dy) = (x - width / 2.0, y - height / 2.0);
+    if radius <= 0.0 || dx.hypot(dy) < radius * SEEK_DEAD_ZONE {
+        return None;
+    }
+    let angle = dx.atan2(-dy);
+    Some(angle.rem_euclid(2.0 * std::f64::consts::PI) / (2.0 * std::f64::consts::PI))
+}
 
 mod imp {
     use super::*;
@@ -9,6 +35,34 @@ mod imp {
     #[derive(Default)]
     pub struct ProgressRingButton {
         pub progress: Cell<f64>,
+        pub buffered: Cell<f64>,
+        pub paused: Cell<bool>,
+        pub dim_when_paused: Cell<bool>,
+        pub countdown: Cell<bool>,
+        pub hide_empty_track: Cell<bool>,
+        pub seekable: Cell<bool>,
+        pub ring_width: Cell<f64>,
+        // Overrides the theme's accent color for the ring when set
+        pub ring_color: RefCell<Option<gdk::RGBA>>,
+        // Fraction of the ring per second to advance by between updates,
+        // from when `progress` was last set
+        pub rate: Cell<f64>,
+        pub progress_set_at: Cell<Option<Instant>>,
+        pub tick_callback: RefCell<Option<gtk::TickCallbackId>>,
+        // Set while loading, from when it started
+        pub loading_since: Cell<Option<Instant>>,
+        pub loading_tick: RefCell<Option<gtk::TickCallbackId>>,
+    }
+
+    impl ProgressRingButton {
+        /// `progress`, moved on by however long it's been playing since.
+        pub fn displayed_progress(&self) -> f64 {
+            let elapsed = self
+                .progress_set_at
+                .get()
+                .map_or(0.0, |at| at.elapsed().as_secs_f64());
+            (self.progress.get() + self.rate.get() * elapsed).clamp(0.0, 1.0)
+        }
     }
 
     #[glib::object_subclass]
@@ -24,8 +78,69 @@ mod imp {
     }
 
     impl ObjectImpl for ProgressRingButton {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: OnceLock<Vec<glib::ParamSpec>> = OnceLock::new();
+            PROPERTIES.get_or_init(|| {
+                vec![
+                    glib::ParamSpecDouble::builder("progress")
+                        .minimum(0.0)
+                        .maximum(1.0)
+                        .build(),
+                    glib::ParamSpecDouble::builder("ring-width")
+                        .minimum(1.0)
+                        .maximum(RING_MARGIN * 2.0)
+                        .default_value(DEFAULT_RING_WIDTH)
+                        .build(),
+                    glib::ParamSpecBoxed::builder::<gdk::RGBA>("ring-color").build(),
+                ]
+            })
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "progress" => {
+                    let progress = value.get::<f64>().unwrap_or_default();
+                    self.progress.set(progress.clamp(0.0, 1.0));
+                    self.progress_set_at.set(Some(Instant::now()));
+                    self.obj().queue_draw();
+                }
+                "ring-width" => {
+                    self.ring_width
+                        .set(value.get().unwrap_or(DEFAULT_RING_WIDTH));
+                    self.obj().queue_draw();
+                }
+                "ring-color" => {
+                    self.ring_color.replace(value.get().unwrap_or_default());
+                    self.obj().queue_draw();
+                }
+                _ => unreachable!("unknown property {}", pspec.name()),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "progress" => self.progress.get().to_value(),
+                "ring-width" => self.ring_width.get().to_value(),
+                "ring-color" => self.ring_color.borrow().to_value(),
+                _ => unreachable!("unknown property {}", pspec.name()),
+            }
+        }
+
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+            SIGNALS.get_or_init(|| {
+                vec![
+                    Signal::builder("clicked").build(),
+                    Signal::builder("seek-requested")
+                        .param_types([f64::static_type()])
+                        .build(),
+                ]
+            })
+        }
+
         fn constructed(&self) {
             self.parent_constructed();
+            self.ring_width.set(DEFAULT_RING_WIDTH);
             let obj = self.obj();
 
             // Create the button child
@@ -37,6 +152,40 @@ mod imp {
                 .build();
 
             button.set_parent(&*obj);
+            let widget = obj.downgrade();
+            button.connect_clicked(move |_| {
+                if let Some(widget) = widget.upgrade() {
+                    widget.emit_by_name::<()>("clicked", &[]);
+                }
+            });
+
+            // The ring follows the accent color unless it has its own
+            let widget = obj.downgrade();
+            adw::StyleManager::default().connect_accent_color_rgba_notify(move |_| {
+                if let Some(widget) = widget.upgrade() {
+                    widget.queue_draw();
+                }
+            });
+
+            // Clicks out on the ring seek there instead of reaching the
+            // button, when there's a track to seek in
+            let click = gtk::GestureClick::new();
+            click.set_propagation_phase(gtk::PropagationPhase::Capture);
+            let widget = obj.downgrade();
+            click.connect_pressed(move |gesture, _, x, y| {
+                let Some(widget) = widget.upgrade() else {
+                    return;
+                };
+                if !widget.imp().seekable.get() {
+                    return;
+                }
+                let fraction = ring_fraction(x, y, widget.width() as f64, widget.height() as f64);
+                if let Some(fraction) = fraction {
+                    gesture.set_state(gtk::EventSequenceState::Claimed);
+                    widget.emit_by_name::<()>("seek-requested", &[&fraction]);
+                }
+            });
+            obj.add_controller(click);
         }
 
         fn dispose(&self) {
@@ -51,51 +200,126 @@ mod imp {
             let widget = self.obj();
             let width = widget.width() as f32;
             let height = widget.height() as f32;
-            let progress = self.progress.get() as f32;
+            let progress = self.displayed_progress() as f32;
+            let buffered = self.buffered.get() as f32;
 
             // Draw the child button first
             self.parent_snapshot(snapshot);
 
-            if progress > 0.0 {
+            let empty = progress <= 0.0 && buffered <= 0.0;
+            let loading_since = self.loading_since.get();
+            if loading_since.is_some() || !(empty && self.hide_empty_track.get()) {
                 let center_x = width / 2.0;
                 let center_y = height / 2.0;
-                let radius = (width.min(height) / 2.0) - 4.0; // Leave some margin
-                let line_width = 3.0;
+                let radius = (width.min(height) / 2.0) - RING_MARGIN as f32;
+                let line_width = self.ring_width.get();
 
                 // Create a cairo context
                 let rect = graphene::Rect::new(0.0, 0.0, width, height);
                 let cr = snapshot.append_cairo(&rect);
 
-                // Use the inner button's color so the radial matches the icon
-                let button = widget
-                    .first_child()
-                    .and_downcast::<gtk::Button>()
-                    .expect("First child should be a button");
-                let color = button.style_context().color();
+                // The theme's accent color, unless a color was asked for
+                let color = self
+                    .ring_color
+                    .borrow()
+                    .unwrap_or_else(|| adw::StyleManager::default().accent_color_rgba());
 
-                // Set up cairo for the progress ring
-                cr.set_source_rgba(
-                    color.red() as f64,
-                    color.green() as f64,
-                    color.blue() as f64,
-                    0.8,
-                );
-                cr.set_line_width(line_width as f64);
+                // Fade the ring while paused if requested, so the paused state
+                // reads at a glance.
+                let alpha = if self.paused.get() && self.dim_when_paused.get() {
+                    0.35
+                } else {
+                    0.8
+                };
+
+                cr.set_line_width(line_width);
                 cr.set_line_cap(cairo::LineCap::Round);
 
-                // Draw the progress arc
                 // Start at -90 degrees (top) and go clockwise
                 let start_angle = -std::f64::consts::FRAC_PI_2;
-                let end_angle = start_angle + (2.0 * std::f64::consts::PI * progress as f64);
 
+                // Faint full circle behind everything, so it's clear how
+                // much of the track is left
+                cr.set_source_rgba(
+                    color.red() as f64,
+                    color.green() as f64,
+                    color.blue() as f64,
+                    alpha * 0.15,
+                );
                 cr.arc(
                     center_x as f64,
                     center_y as f64,
                     radius as f64,
-                    start_angle,
-                    end_angle,
+                    0.0,
+                    2.0 * std::f64::consts::PI,
                 );
                 cr.stroke().ok();
+
+                // While loading, a short arc spins round in place of the
+                // progress
+                if let Some(since) = loading_since {
+                    let turns = since.elapsed().as_secs_f64() / LOADING_TURN.as_secs_f64();
+                    let from = start_angle + 2.0 * std::f64::consts::PI * turns.fract();
+                    cr.set_source_rgba(
+                        color.red() as f64,
+                        color.green() as f64,
+                        color.blue() as f64,
+                        alpha,
+                    );
+                    cr.arc(
+                        center_x as f64,
+                        center_y as f64,
+                        radius as f64,
+                        from,
+                        from + std::f64::consts::FRAC_PI_2,
+                    );
+                    cr.stroke().ok();
+                    return;
+                }
+
+                // Buffered-ahead arc, lighter and behind the played arc. In
+                // countdown mode the remaining arc covers it, so it's skipped.
+                if buffered > progress && !self.countdown.get() {
+                    cr.set_source_rgba(
+                        color.red() as f64,
+                        color.green() as f64,
+                        color.blue() as f64,
+                        alpha * 0.35,
+                    );
+                    let end_angle = start_angle + (2.0 * std::f64::consts::PI * buffered as f64);
+                    cr.arc(
+                        center_x as f64,
+                        center_y as f64,
+                        radius as f64,
+                        start_angle,
+                        end_angle,
+                    );
+                    cr.stroke().ok();
+                }
+
+                // A countdown ring is full before the track starts playing
+                if progress > 0.0 || self.countdown.get() {
+                    // Set up cairo for the progress ring
+                    cr.set_source_rgba(
+                        color.red() as f64,
+                        color.green() as f64,
+                        color.blue() as f64,
+                        alpha,
+                    );
+
+                    // Draw the progress arc, or in countdown mode the part of
+                    // the track still to play, which empties towards the top
+                    let progress_angle =
+                        start_angle + (2.0 * std::f64::consts::PI * progress as f64);
+                    let (from, to) = if self.countdown.get() {
+                        (progress_angle, start_angle + 2.0 * std::f64::consts::PI)
+                    } else {
+                        (start_angle, progress_angle)
+                    };
+
+                    cr.arc(center_x as f64, center_y as f64, radius as f64, from, to);
+                    cr.stroke().ok();
+                }
             }
         }
     }
@@ -111,13 +335,71 @@ impl ProgressRingButton {
         glib::Object::new()
     }
 
+    /// Fraction of the track played, from 0.0 to 1.0. Also the `progress`
+    /// property, so it can be animated or bound.
+    pub fn progress(&self) -> f64 {
+        self.property("progress")
+    }
+
     pub fn set_progress(&self, progress: f64) {
-        let progress = progress.clamp(0.0, 1.0);
-        self.imp().progress.set(progress);
+        // Out-of-range values would be rejected by the property
+        self.set_property("progress", progress.clamp(0.0, 1.0));
+    }
+
+    /// Keep the ring moving between `set_progress` calls, by `per_second`
+    /// of a full turn each second (one over the track length while
+    /// playing). Zero holds it still.
+    pub fn set_progress_rate(&self, per_second: f64) {
+        let imp = self.imp();
+        imp.rate.set(per_second.max(0.0));
+
+        let mut tick_callback = imp.tick_callback.borrow_mut();
+        if per_second > 0.0 {
+            if tick_callback.is_none() {
+                *tick_callback = Some(self.add_tick_callback(|widget, _| {
+                    widget.queue_draw();
+                    glib::ControlFlow::Continue
+                }));
+            }
+        } else if let Some(id) = tick_callback.take() {
+            id.remove();
+        }
+    }
+
+    /// How far ahead the stream has buffered, as a fraction of the track.
+    /// Zero hides the buffered arc.
+    pub fn set_buffered(&self, buffered: f64) {
+        let buffered = buffered.clamp(0.0, 1.0);
+        self.imp().buffered.set(buffered);
         self.queue_draw();
     }
 
-    pub fn button(&self) -> gtk::Button {
+    /// Spin a short arc round the ring in place of the progress, e.g.
+    /// while the art downloads. Turning it off shows the progress again.
+    pub fn set_loading(&self, loading: bool) {
+        let imp = self.imp();
+        if loading == imp.loading_since.get().is_some() {
+            return;
+        }
+        imp.loading_since.set(loading.then(Instant::now));
+
+        let mut loading_tick = imp.loading_tick.borrow_mut();
+        if loading {
+            *loading_tick = Some(self.add_tick_callback(|widget, _| {
+                widget.queue_draw();
+                glib::ControlFlow::Continue
+            }));
+        } else if let Some(id) = loading_tick.take() {
+            id.remove();
+        }
+        self.queue_draw();
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.imp().loading_since.get().is_some()
+    }
+
+    fn button(&self) -> gtk::Button {
         self.first_child()
             .and_downcast::<gtk::Button>()
             .expect("First child should be a button")
@@ -127,7 +409,69 @@ impl ProgressRingButton {
         self.button().set_icon_name(icon_name);
     }
 
+    pub fn icon_name(&self) -> Option<glib::GString> {
+        self.button().icon_name()
+    }
+
+    pub fn set_dim_when_paused(&self, dim: bool) {
+        self.imp().dim_when_paused.set(dim);
+        self.queue_draw();
+    }
+
+    /// Draw nothing at all, not even the faint track, until there's some
+    /// progress or buffering to show.
+    pub fn set_hide_empty_track(&self, hide: bool) {
+        self.imp().hide_empty_track.set(hide);
+        self.queue_draw();
+    }
+
+    /// Draw the time remaining instead of the time played: a full ring at
+    /// the start of the track that empties as it plays.
+    pub fn set_countdown_mode(&self, countdown: bool) {
+        self.imp().countdown.set(countdown);
+        self.queue_draw();
+    }
+
+    /// Thickness of the ring in pixels. Also the `ring-width` property.
+    pub fn set_ring_width(&self, width: f64) {
+        self.set_property("ring-width", width.clamp(1.0, RING_MARGIN * 2.0));
+    }
+
+    /// Color of the ring, or `None` for the theme's accent color. Also the
+    /// `ring-color` property.
+    pub fn set_ring_color(&self, color: Option<&gdk::RGBA>) {
+        self.set_property("ring-color", color);
+    }
+
+    /// Whether clicks on the ring should seek. Off for streams and players
+    /// that can't seek, so the whole button plays and pauses.
+    pub fn set_seekable(&self, seekable: bool) {
+        self.imp().seekable.set(seekable);
+    }
+
+    /// Called when the button in the middle is clicked.
+    pub fn connect_clicked<F: Fn(&Self) + 'static>(&self, f: F) {
+        self.connect_local("clicked", false, move |values| {
+            let widget = values[0].get::<Self>().ok()?;
+            f(&widget);
+            None
+        });
+    }
+
+    /// Called with the fraction of the track clicked on along the ring.
+    pub fn connect_seek_requested<F: Fn(&Self, f64) + 'static>(&self, f: F) {
+        self.connect_local("seek-requested", false, move |values| {
+            let widget = values[0].get::<Self>().ok()?;
+            let fraction = values[1].get::<f64>().ok()?;
+            f(&widget, fraction);
+            None
+        });
+    }
+
     pub fn set_paused_style(&self, is_paused: bool) {
+        if self.imp().paused.replace(is_paused) != is_paused {
+            self.queue_draw();
+        }
         let button = self.button();
         button.remove_css_class("suggested-action");
         button.add_css_class("play-pause");
@@ -144,3 +488,40 @@ impl Default for ProgressRingButton {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::tests::with_gtk;
+    use std::rc::Rc;
+
+    #[test]
+    fn clicks_on_the_ring_map_clockwise_from_the_top() {
+        let fraction = |x, y| ring_fraction(x, y, 48.0, 48.0);
+        assert_eq!(fraction(24.0, 4.0), Some(0.0));
+        assert_eq!(fraction(44.0, 24.0), Some(0.25));
+        assert_eq!(fraction(24.0, 44.0), Some(0.5));
+        assert_eq!(fraction(4.0, 24.0), Some(0.75));
+        // The middle still plays and pauses
+        assert_eq!(fraction(24.0, 24.0), None);
+        assert_eq!(fraction(30.0, 24.0), None);
+    }
+
+    #[test]
+    fn ring_progress_is_a_notifying_property() {
+        with_gtk(|| {
+            let ring = ProgressRingButton::new();
+            let notified = Rc::new(Cell::new(0));
+            ring.connect_notify_local(Some("progress"), {
+                let notified = notified.clone();
+                move |_, _| notified.set(notified.get() + 1)
+            });
+
+            ring.set_progress(1.5);
+            assert_eq!(ring.progress(), 1.0);
+            ring.set_property("progress", 0.25);
+            assert_eq!(ring.progress(), 0.25);
+            assert_eq!(notified.get(), 2);
+        });
+    }
+}
@@ -0,0 +1,147 @@
+use dbus::blocking::Connection;
+use dbus::message::MatchRule;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::mpris_client::{MediaInfo, MprisClient, PlayerStatus};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SessionEvent {
+    Locked(bool),
+    Asleep(bool),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Response {
+    Pause,
+    Resume,
+}
+
+/// Whether the session is locked or asleep, and whether we paused playback
+/// because of it.
+#[derive(Default)]
+struct SessionState {
+    locked: bool,
+    asleep: bool,
+    paused_by_us: bool,
+}
+
+impl SessionState {
+    /// What to do about `event`, given whether the player is `playing`.
+    /// Only resumes once the session is both unlocked and awake, so waking
+    /// up to a lock screen doesn't start the music.
+    fn handle(&mut self, event: SessionEvent, playing: bool, resume: bool) -> Option<Response> {
+        match event {
+            SessionEvent::Locked(locked) => self.locked = locked,
+            SessionEvent::Asleep(asleep) => self.asleep = asleep,
+        }
+
+        if self.locked || self.asleep {
+            if playing && !self.paused_by_us {
+                self.paused_by_us = true;
+                return Some(Response::Pause);
+            }
+        } else if self.paused_by_us {
+            self.paused_by_us = false;
+            if resume {
+                return Some(Response::Resume);
+            }
+        }
+        None
+    }
+}
+
+/// Pause the player when the session locks or the system suspends, and,
+/// if `resume` is set, start it again once it's unlocked and awake.
+/// `latest_info` tells whether anything is playing. Listens on whichever of
+/// logind and the GNOME / freedesktop screensaver are available.
+pub fn spawn(client: MprisClient, latest_info: Arc<Mutex<MediaInfo>>, resume: bool) {
+    let state = Arc::new(Mutex::new(SessionState::default()));
+    let on_event = move |event: SessionEvent| {
+        let playing = latest_info
+            .lock()
+            .map(|info| info.status == PlayerStatus::Playing)
+            .unwrap_or(false);
+        let response = match state.lock() {
+            Ok(mut state) => state.handle(event, playing, resume),
+            Err(_) => return,
+        };
+        let result = match response {
+            Some(Response::Pause) => client.pause(),
+            Some(Response::Resume) => client.play(),
+            None => Ok(()),
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to send command for session change: {}", e);
+        }
+    };
+
+    let for_session = on_event.clone();
+    thread::spawn(move || {
+        let connection = match Connection::new_session() {
+            Ok(connection) => connection,
+            Err(e) => {
+                eprintln!("Not watching for screen lock: {}", e);
+                return;
+            }
+        };
+        for interface in ["org.gnome.ScreenSaver", "org.freedesktop.ScreenSaver"] {
+            let on_event = for_session.clone();
+            let rule = MatchRule::new_signal(interface, "ActiveChanged");
+            let added = connection.add_match(rule, move |(active,): (bool,), _, _| {
+                on_event(SessionEvent::Locked(active));
+                true
+            });
+            if let Err(e) = added {
+                eprintln!("Not watching {} for screen lock: {}", interface, e);
+            }
+        }
+        while connection.process(Duration::from_secs(60)).is_ok() {}
+    });
+
+    thread::spawn(move || {
+        let connection = match Connection::new_system() {
+            Ok(connection) => connection,
+            Err(e) => {
+                eprintln!("Not watching for suspend: {}", e);
+                return;
+            }
+        };
+        let rule = MatchRule::new_signal("org.freedesktop.login1.Manager", "PrepareForSleep");
+        let added = connection.add_match(rule, move |(sleeping,): (bool,), _, _| {
+            on_event(SessionEvent::Asleep(sleeping));
+            true
+        });
+        if let Err(e) = added {
+            eprintln!("Not watching for suspend: {}", e);
+            return;
+        }
+        while connection.process(Duration::from_secs(60)).is_ok() {}
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resumes_only_when_unlocked_and_awake() {
+        let mut state = SessionState::default();
+        assert_eq!(
+            state.handle(SessionEvent::Locked(true), true, true),
+            Some(Response::Pause)
+        );
+        // Already paused by the lock; nothing more to do
+        assert_eq!(state.handle(SessionEvent::Asleep(true), false, true), None);
+        assert_eq!(state.handle(SessionEvent::Asleep(false), false, true), None);
+        assert_eq!(
+            state.handle(SessionEvent::Locked(false), false, true),
+            Some(Response::Resume)
+        );
+
+        // Wasn't playing, so it isn't started on unlock
+        assert_eq!(state.handle(SessionEvent::Locked(true), false, true), None);
+        assert_eq!(state.handle(SessionEvent::Locked(false), false, true), None);
+    }
+}
@@ -0,0 +1,172 @@
+use gtk::cairo;
+use gtk::gdk;
+use gtk::prelude::*;
+use std::f64::consts::PI;
+
+use crate::mpris_client::MediaInfo;
+
+const CARD_WIDTH: i32 = 600;
+const CARD_HEIGHT: i32 = 780;
+const CARD_PADDING: f64 = 44.0;
+const ART_RADIUS: f64 = 24.0;
+
+/// Render a "now playing" card with the cover, title, artist and album.
+pub fn render(info: &MediaInfo, art: Option<&gdk::Texture>) -> anyhow::Result<gdk::Texture> {
+    let mut surface = cairo::ImageSurface::create(cairo::Format::ARgb32, CARD_WIDTH, CARD_HEIGHT)?;
+
+    {
+        let cr = cairo::Context::new(&surface)?;
+
+        // Dark background with a subtle vertical gradient
+        let background = cairo::LinearGradient::new(0.0, 0.0, 0.0, CARD_HEIGHT as f64);
+        background.add_color_stop_rgb(0.0, 0.16, 0.15, 0.20);
+        background.add_color_stop_rgb(1.0, 0.08, 0.08, 0.10);
+        cr.set_source(&background)?;
+        cr.paint()?;
+
+        let art_size = CARD_WIDTH as f64 - 2.0 * CARD_PADDING;
+        rounded_rect(
+            &cr,
+            CARD_PADDING,
+            CARD_PADDING,
+            art_size,
+            art_size,
+            ART_RADIUS,
+        );
+
+        match art.map(texture_to_surface).transpose()? {
+            Some(art_surface) => {
+                // Scale to cover the square, cropping the longer side
+                let scale = (art_size / art_surface.width() as f64)
+                    .max(art_size / art_surface.height() as f64);
+                let offset_x = (art_size - art_surface.width() as f64 * scale) / 2.0;
+                let offset_y = (art_size - art_surface.height() as f64 * scale) / 2.0;
+
+                cr.save()?;
+                cr.clip();
+                cr.translate(CARD_PADDING + offset_x, CARD_PADDING + offset_y);
+                cr.scale(scale, scale);
+                cr.set_source_surface(&art_surface, 0.0, 0.0)?;
+                cr.paint()?;
+                cr.restore()?;
+            }
+            None => {
+                // Placeholder: shaded square with the artist/title initial
+                cr.set_source_rgba(1.0, 1.0, 1.0, 0.08);
+                cr.fill()?;
+
+                let initial = info
+                    .artist
+                    .chars()
+                    .chain(info.title.chars())
+                    .next()
+                    .unwrap_or('?')
+                    .to_uppercase()
+                    .to_string();
+                cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+                cr.set_font_size(art_size / 3.0);
+                let extents = cr.text_extents(&initial)?;
+                cr.move_to(
+                    CARD_PADDING + (art_size - extents.width()) / 2.0 - extents.x_bearing(),
+                    CARD_PADDING + (art_size - extents.height()) / 2.0 - extents.y_bearing(),
+                );
+                cr.set_source_rgba(1.0, 1.0, 1.0, 0.6);
+                cr.show_text(&initial)?;
+            }
+        }
+
+        let text_width = art_size;
+        let mut baseline = CARD_PADDING + art_size + 64.0;
+
+        cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+        cr.set_font_size(34.0);
+        cr.set_source_rgba(1.0, 1.0, 1.0, 1.0);
+        cr.move_to(CARD_PADDING, baseline);
+        cr.show_text(&fit_text(&cr, &info.title, text_width)?)?;
+
+        cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Normal);
+        if !info.artist.is_empty() {
+            baseline += 44.0;
+            cr.set_font_size(24.0);
+            cr.set_source_rgba(1.0, 1.0, 1.0, 0.7);
+            cr.move_to(CARD_PADDING, baseline);
+            cr.show_text(&fit_text(&cr, &info.artist, text_width)?)?;
+        }
+
+        if !info.album.is_empty() {
+            baseline += 36.0;
+            cr.set_font_size(18.0);
+            cr.set_source_rgba(1.0, 1.0, 1.0, 0.55);
+            cr.move_to(CARD_PADDING, baseline);
+            cr.show_text(&fit_text(&cr, &info.album, text_width)?)?;
+        }
+    }
+
+    surface.flush();
+    let stride = surface.stride() as usize;
+    let data = surface.data()?.to_vec();
+
+    Ok(gdk::MemoryTexture::new(
+        CARD_WIDTH,
+        CARD_HEIGHT,
+        CAIRO_MEMORY_FORMAT,
+        &glib::Bytes::from_owned(data),
+        stride,
+    )
+    .upcast())
+}
+
+// Cairo's ARGB32 is native-endian premultiplied, which is also what
+// `Texture::download` produces.
+#[cfg(target_endian = "little")]
+const CAIRO_MEMORY_FORMAT: gdk::MemoryFormat = gdk::MemoryFormat::B8g8r8a8Premultiplied;
+#[cfg(target_endian = "big")]
+const CAIRO_MEMORY_FORMAT: gdk::MemoryFormat = gdk::MemoryFormat::A8r8g8b8Premultiplied;
+
+fn texture_to_surface(texture: &gdk::Texture) -> anyhow::Result<cairo::ImageSurface> {
+    let width = texture.width();
+    let height = texture.height();
+    let stride = cairo::Format::ARgb32.stride_for_width(width as u32)?;
+    let mut data = vec![0u8; stride as usize * height as usize];
+    texture.download(&mut data, stride as usize);
+    Ok(cairo::ImageSurface::create_for_data(
+        data,
+        cairo::Format::ARgb32,
+        width,
+        height,
+        stride,
+    )?)
+}
+
+/// Truncate `text` with an ellipsis so it fits in `max_width` at the
+/// current font settings.
+fn fit_text(cr: &cairo::Context, text: &str, max_width: f64) -> anyhow::Result<String> {
+    if cr.text_extents(text)?.x_advance() <= max_width {
+        return Ok(text.to_string());
+    }
+
+    let mut chars: Vec<char> = text.chars().collect();
+    while !chars.is_empty() {
+        chars.pop();
+        let candidate = format!("{}…", chars.iter().collect::<String>().trim_end());
+        if cr.text_extents(&candidate)?.x_advance() <= max_width {
+            return Ok(candidate);
+        }
+    }
+    Ok(String::new())
+}
+
+fn rounded_rect(cr: &cairo::Context, x: f64, y: f64, width: f64, height: f64, radius: f64) {
+    cr.new_sub_path();
+    cr.arc(x + width - radius, y + radius, radius, -PI / 2.0, 0.0);
+    cr.arc(
+        x + width - radius,
+        y + height - radius,
+        radius,
+        0.0,
+        PI / 2.0,
+    );
+    cr.arc(x + radius, y + height - radius, radius, PI / 2.0, PI);
+    cr.arc(x + radius, y + radius, radius, PI, 3.0 * PI / 2.0);
+    cr.close_path();
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::util::write_atomically;
+
+/// What Empress remembers between launches by itself, kept apart from the
+/// config file so saving never rewrites the user's settings.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    /// Window size when it was last closed.
+    pub window_width: Option<i32>,
+    pub window_height: Option<i32>,
+    /// Player last picked in the player menu; unset for Auto.
+    pub preferred_player: Option<String>,
+}
+
+impl State {
+    pub fn path() -> PathBuf {
+        glib::user_config_dir().join("empress").join("state.toml")
+    }
+
+    /// Load the saved state, or start fresh when it is missing or can't be
+    /// parsed.
+    pub fn load() -> Self {
+        let path = Self::path();
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+
+        match toml::from_str(&contents) {
+            Ok(state) => state,
+            Err(e) => {
+                eprintln!("Ignoring unreadable state {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        write_atomically(&path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Load, change with `f`, and save again, logging any failure.
+    pub fn update(f: impl FnOnce(&mut State)) {
+        let mut state = Self::load();
+        f(&mut state);
+        if let Err(e) = state.save() {
+            eprintln!("Failed to save state to {}: {}", Self::path().display(), e);
+        }
+    }
+}
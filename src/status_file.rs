@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+
+use crate::mpris_client::{MediaInfo, PlayerStatus};
+use crate::util::{format_time, write_atomically};
+
+/// A one-line summary of playback kept in a file, for status bars like
+/// waybar or polybar that read from files.
+pub struct StatusFile {
+    path: PathBuf,
+    template: String,
+    last_written: Option<String>,
+}
+
+impl StatusFile {
+    pub fn new(path: PathBuf, template: String) -> Self {
+        Self {
+            path,
+            template,
+            last_written: None,
+        }
+    }
+
+    /// Rewrite the file if the line for `info` differs from the last one.
+    pub fn update(&mut self, info: &MediaInfo) {
+        let line = render(&self.template, info);
+        if self.last_written.as_deref() == Some(line.as_str()) {
+            return;
+        }
+
+        match write_atomically(&self.path, format!("{}\n", line)) {
+            Ok(()) => self.last_written = Some(line),
+            Err(e) => eprintln!("Failed to write status file {}: {}", self.path.display(), e),
+        }
+    }
+}
+
+/// Fill in `template`'s placeholders: `{icon}`, `{status}`, `{title}`,
+/// `{artist}`, `{album}`, `{player}`, `{position}` and `{length}`. Empty
+/// when nothing is playing.
+pub fn render(template: &str, info: &MediaInfo) -> String {
+    if info.title.is_empty() && info.player_identity.is_empty() {
+        return String::new();
+    }
+
+    let (icon, status) = match info.status {
+        PlayerStatus::Playing => ("▶", "Playing"),
+        PlayerStatus::Paused => ("⏸", "Paused"),
+        PlayerStatus::Stopped => ("⏹", "Stopped"),
+    };
+    let time = |time: Option<_>| time.map(format_time).unwrap_or_default();
+    let value = |name: &str| match name {
+        "icon" => Some(icon.to_string()),
+        "status" => Some(status.to_string()),
+        "title" => Some(info.title.clone()),
+        "artist" => Some(info.artist.clone()),
+        "album" => Some(info.album.clone()),
+        "player" => Some(info.player_identity.clone()),
+        "position" => Some(time(info.position)),
+        "length" => Some(time(info.length)),
+        _ => None,
+    };
+
+    // One pass, so a title containing "{artist}" is left as it is
+    let mut line = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        line.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let filled = rest
+            .find('}')
+            .and_then(|end| Some((value(&rest[1..end])?, end)));
+        match filled {
+            Some((text, end)) => {
+                line.push_str(&text);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                line.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    line.push_str(rest);
+    line.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders_are_filled_in() {
+        let info = MediaInfo {
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            player_identity: "Player".to_string(),
+            status: PlayerStatus::Playing,
+            ..Default::default()
+        };
+        assert_eq!(
+            render("{icon} {title} - {artist}", &info),
+            "▶ Song - Artist"
+        );
+        assert_eq!(render("{icon} {title}", &MediaInfo::default()), "");
+    }
+
+    #[test]
+    fn placeholders_are_filled_in_one_pass() {
+        let info = MediaInfo {
+            title: "{artist}".to_string(),
+            artist: "Artist".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(render("{title} {unknown} {", &info), "{artist} {unknown} {");
+    }
+}
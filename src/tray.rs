@@ -0,0 +1,431 @@
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::blocking::Connection;
+use dbus::channel::{MatchingReceiver, Sender as _};
+use dbus::message::MatchRule;
+use dbus::strings::ErrorName;
+use dbus::Message;
+use std::ffi::CString;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::mpris_client::{MediaInfo, MprisClient, PlayerStatus};
+
+const ITEM_PATH: &str = "/StatusNotifierItem";
+const ITEM_INTERFACE: &str = "org.kde.StatusNotifierItem";
+const MENU_PATH: &str = "/MenuBar";
+const MENU_INTERFACE: &str = "com.canonical.dbusmenu";
+/// How often to check whether the icon, tooltip or menu need updating.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// What the tray asks of the window, which only the main thread can do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrayEvent {
+    ToggleWindow,
+    /// Show the window and bring it to the front, leaving it up if it
+    /// already is.
+    Present,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MenuItem {
+    Track = 1,
+    TrackSeparator,
+    PlayPause,
+    Next,
+    Previous,
+    Separator,
+    ToggleWindow,
+}
+
+impl MenuItem {
+    const ALL: [MenuItem; 7] = [
+        MenuItem::Track,
+        MenuItem::TrackSeparator,
+        MenuItem::PlayPause,
+        MenuItem::Next,
+        MenuItem::Previous,
+        MenuItem::Separator,
+        MenuItem::ToggleWindow,
+    ];
+
+    fn from_id(id: i32) -> Option<Self> {
+        Self::ALL.into_iter().find(|item| *item as i32 == id)
+    }
+
+    /// The playback item a `tray_raise_on` entry names.
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "play" | "play_pause" => Some(MenuItem::PlayPause),
+            "next" => Some(MenuItem::Next),
+            "prev" | "previous" => Some(MenuItem::Previous),
+            _ => None,
+        }
+    }
+
+    fn label(self, info: &MediaInfo) -> Option<String> {
+        let label = match self {
+            // Underscores would otherwise be taken as mnemonics
+            MenuItem::Track => return Some(track_label(info).replace('_', "__")),
+            MenuItem::PlayPause if info.status == PlayerStatus::Playing => "Pause",
+            MenuItem::PlayPause => "Play",
+            MenuItem::Next => "Next",
+            MenuItem::Previous => "Previous",
+            MenuItem::TrackSeparator | MenuItem::Separator => return None,
+            MenuItem::ToggleWindow => "Show/Hide Window",
+        };
+        Some(label.to_string())
+    }
+
+    fn properties(self, info: &MediaInfo) -> PropMap {
+        let mut properties = PropMap::new();
+        match self.label(info) {
+            Some(label) => properties.insert("label".to_string(), variant(label)),
+            None => properties.insert("type".to_string(), variant("separator".to_string())),
+        };
+        properties
+    }
+}
+
+/// The current track as the tooltip and menu show it.
+fn track_label(info: &MediaInfo) -> String {
+    match (info.title.is_empty(), info.artist.is_empty()) {
+        (true, _) => "No media playing".to_string(),
+        (false, true) => info.title.clone(),
+        (false, false) => format!("{} — {}", info.title, info.artist),
+    }
+}
+
+/// A dbusmenu layout node: id, properties and child nodes.
+type Layout = (i32, PropMap, Vec<Variant<Box<dyn RefArg>>>);
+
+fn variant<T: RefArg + 'static>(value: T) -> Variant<Box<dyn RefArg>> {
+    Variant(Box::new(value))
+}
+
+/// The menu, or just one item of it when `parent` names one, since items
+/// have no submenus.
+fn layout(parent: i32, info: &MediaInfo) -> Option<Layout> {
+    if parent != 0 {
+        let item = MenuItem::from_id(parent)?;
+        return Some((parent, item.properties(info), Vec::new()));
+    }
+
+    let children = MenuItem::ALL
+        .into_iter()
+        .map(|item| {
+            let node: Layout = (item as i32, item.properties(info), Vec::new());
+            variant(node)
+        })
+        .collect();
+    let mut root = PropMap::new();
+    root.insert(
+        "children-display".to_string(),
+        variant("submenu".to_string()),
+    );
+    Some((0, root, children))
+}
+
+fn item_properties(info: &MediaInfo) -> PropMap {
+    let playing = info.status == PlayerStatus::Playing;
+    let icon_name = if playing {
+        "media-playback-start"
+    } else {
+        "media-playback-pause"
+    };
+    let description = track_label(info);
+    let pixmaps: Vec<(i32, i32, Vec<u8>)> = Vec::new();
+
+    let mut properties = PropMap::new();
+    let mut insert = |name: &str, value| properties.insert(name.to_string(), value);
+    insert("Category", variant("ApplicationStatus".to_string()));
+    insert("Id", variant("empress".to_string()));
+    insert("Title", variant("Empress".to_string()));
+    insert("Status", variant("Active".to_string()));
+    insert("IconName", variant(icon_name.to_string()));
+    insert(
+        "ToolTip",
+        variant((String::new(), pixmaps, "Empress".to_string(), description)),
+    );
+    insert("ItemIsMenu", variant(false));
+    insert("Menu", variant(dbus::Path::from(MENU_PATH)));
+    properties
+}
+
+fn menu_properties() -> PropMap {
+    let mut properties = PropMap::new();
+    properties.insert("Version".to_string(), variant(3u32));
+    properties.insert("TextDirection".to_string(), variant("ltr".to_string()));
+    properties.insert("Status".to_string(), variant("normal".to_string()));
+    properties.insert("IconThemePath".to_string(), variant(Vec::<String>::new()));
+    properties
+}
+
+/// Answers calls to the status notifier item and its menu.
+struct Handler {
+    client: MprisClient,
+    latest_info: Arc<Mutex<MediaInfo>>,
+    events: Sender<TrayEvent>,
+    revision: Arc<AtomicU32>,
+    /// Playback items that bring up the window as well.
+    raising: Vec<MenuItem>,
+}
+
+impl Handler {
+    fn reply(&self, msg: &Message) -> Message {
+        let info = self
+            .latest_info
+            .lock()
+            .map(|info| info.clone())
+            .unwrap_or_default();
+        let path = msg.path();
+        let on_menu = path.as_deref() == Some(MENU_PATH);
+        let interface = msg.interface();
+        let member = msg.member();
+
+        match (interface.as_deref(), member.as_deref()) {
+            (Some("org.freedesktop.DBus.Properties"), Some(member)) => {
+                let properties = if on_menu {
+                    menu_properties()
+                } else {
+                    item_properties(&info)
+                };
+                match member {
+                    "GetAll" => msg.method_return().append1(properties),
+                    "Get" => {
+                        let name = msg.read2::<&str, &str>().map(|(_, name)| name);
+                        match name.ok().and_then(|name| properties.get(name)) {
+                            Some(value) => msg.method_return().append1(variant(value.box_clone())),
+                            None => error(msg, "org.freedesktop.DBus.Error.UnknownProperty"),
+                        }
+                    }
+                    _ => error(msg, "org.freedesktop.DBus.Error.UnknownMethod"),
+                }
+            }
+            (Some(ITEM_INTERFACE), Some(member)) => {
+                match member {
+                    "Activate" => {
+                        let _ = self.events.send(TrayEvent::ToggleWindow);
+                    }
+                    "SecondaryActivate" => self.activate(MenuItem::PlayPause),
+                    _ => {}
+                }
+                msg.method_return()
+            }
+            (Some(MENU_INTERFACE), Some("GetLayout")) => {
+                let parent = msg.read1::<i32>().unwrap_or(0);
+                match layout(parent, &info) {
+                    Some(layout) => msg
+                        .method_return()
+                        .append2(self.revision.load(Ordering::SeqCst), layout),
+                    None => error(msg, "org.freedesktop.DBus.Error.InvalidArgs"),
+                }
+            }
+            (Some(MENU_INTERFACE), Some("GetGroupProperties")) => {
+                let ids = msg.read1::<Vec<i32>>().unwrap_or_default();
+                let items: Vec<(i32, PropMap)> = MenuItem::ALL
+                    .into_iter()
+                    .filter(|item| ids.is_empty() || ids.contains(&(*item as i32)))
+                    .map(|item| (item as i32, item.properties(&info)))
+                    .collect();
+                msg.method_return().append1(items)
+            }
+            (Some(MENU_INTERFACE), Some("GetProperty")) => {
+                let value = msg.read2::<i32, &str>().ok().and_then(|(id, name)| {
+                    let mut properties = MenuItem::from_id(id)?.properties(&info);
+                    properties.remove(name)
+                });
+                match value {
+                    Some(value) => msg.method_return().append1(value),
+                    None => error(msg, "org.freedesktop.DBus.Error.InvalidArgs"),
+                }
+            }
+            (Some(MENU_INTERFACE), Some("Event")) => {
+                if let Ok((id, "clicked")) = msg.read2::<i32, &str>() {
+                    if let Some(item) = MenuItem::from_id(id) {
+                        self.activate(item);
+                    }
+                }
+                msg.method_return()
+            }
+            (Some(MENU_INTERFACE), Some("EventGroup")) => {
+                type Event<'a> = (i32, &'a str, Variant<Box<dyn RefArg>>, u32);
+                for (id, event, _, _) in msg.read1::<Vec<Event>>().unwrap_or_default() {
+                    if let (Some(item), "clicked") = (MenuItem::from_id(id), event) {
+                        self.activate(item);
+                    }
+                }
+                msg.method_return().append1(Vec::<i32>::new())
+            }
+            (Some(MENU_INTERFACE), Some("AboutToShow")) => msg.method_return().append1(false),
+            (Some(MENU_INTERFACE), Some("AboutToShowGroup")) => msg
+                .method_return()
+                .append2(Vec::<i32>::new(), Vec::<i32>::new()),
+            _ => error(msg, "org.freedesktop.DBus.Error.UnknownMethod"),
+        }
+    }
+
+    fn activate(&self, item: MenuItem) {
+        let result = match item {
+            MenuItem::PlayPause => self.client.play_pause(),
+            MenuItem::Next => self.client.next(),
+            MenuItem::Previous => self.client.previous(),
+            MenuItem::Track => {
+                let _ = self.events.send(TrayEvent::Present);
+                Ok(())
+            }
+            MenuItem::ToggleWindow => {
+                let _ = self.events.send(TrayEvent::ToggleWindow);
+                Ok(())
+            }
+            MenuItem::TrackSeparator | MenuItem::Separator => Ok(()),
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to send command from the tray: {}", e);
+        }
+        if self.raising.contains(&item) {
+            let _ = self.events.send(TrayEvent::Present);
+        }
+    }
+}
+
+fn error(msg: &Message, name: &'static str) -> Message {
+    let text = CString::new(name).unwrap_or_default();
+    msg.error(&ErrorName::from(name), &text)
+}
+
+/// Show a status icon with playback controls, for trays that implement
+/// StatusNotifierItem. Clicking the icon, or Show/Hide Window in its menu,
+/// sends `TrayEvent::ToggleWindow` on the returned receiver. Clicking the
+/// track title, or one of the playback items named in `raise_on`, sends
+/// `TrayEvent::Present`.
+pub fn spawn(
+    client: MprisClient,
+    latest_info: Arc<Mutex<MediaInfo>>,
+    raise_on: &[String],
+) -> Receiver<TrayEvent> {
+    let (events, event_receiver) = channel();
+    let raising = raise_on
+        .iter()
+        .filter_map(|name| MenuItem::from_name(name))
+        .collect();
+    thread::spawn(move || {
+        if let Err(e) = run(client, latest_info, events, raising) {
+            eprintln!("Not showing a tray icon: {}", e);
+        }
+    });
+    event_receiver
+}
+
+fn run(
+    client: MprisClient,
+    latest_info: Arc<Mutex<MediaInfo>>,
+    events: Sender<TrayEvent>,
+    raising: Vec<MenuItem>,
+) -> anyhow::Result<()> {
+    let connection = Connection::new_session()?;
+    let name = format!("org.kde.StatusNotifierItem-{}-1", std::process::id());
+    connection.request_name(name.as_str(), false, true, false)?;
+
+    let revision = Arc::new(AtomicU32::new(1));
+    let handler = Handler {
+        client,
+        latest_info: latest_info.clone(),
+        events,
+        revision: revision.clone(),
+        raising,
+    };
+    connection.start_receive(
+        MatchRule::new_method_call(),
+        Box::new(move |msg, connection| {
+            let _ = connection.send(handler.reply(&msg));
+            true
+        }),
+    );
+
+    connection
+        .with_proxy(
+            "org.kde.StatusNotifierWatcher",
+            "/StatusNotifierWatcher",
+            Duration::from_secs(5),
+        )
+        .method_call::<(), _, _, _>(
+            "org.kde.StatusNotifierWatcher",
+            "RegisterStatusNotifierItem",
+            (name.as_str(),),
+        )?;
+
+    // Tell the tray whenever what it shows goes out of date
+    let mut shown: Option<(PlayerStatus, String, String)> = None;
+    loop {
+        connection.process(REFRESH_INTERVAL)?;
+
+        let current = latest_info
+            .lock()
+            .map(|info| (info.status.clone(), info.title.clone(), info.artist.clone()))
+            .unwrap_or_default();
+        if shown.as_ref() == Some(&current) {
+            continue;
+        }
+        if shown.is_some() {
+            let revision = revision.fetch_add(1, Ordering::SeqCst) + 1;
+            for signal in ["NewIcon", "NewToolTip"] {
+                let message = Message::new_signal(ITEM_PATH, ITEM_INTERFACE, signal)
+                    .map_err(anyhow::Error::msg)?;
+                let _ = connection.send(message);
+            }
+            let message = Message::new_signal(MENU_PATH, MENU_INTERFACE, "LayoutUpdated")
+                .map_err(anyhow::Error::msg)?
+                .append2(revision, 0i32);
+            let _ = connection.send(message);
+        }
+        shown = Some(current);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label(item: MenuItem, info: &MediaInfo) -> Option<String> {
+        let (_, properties, _) = layout(item as i32, info).unwrap();
+        properties
+            .get("label")
+            .and_then(|v| v.as_str().map(str::to_string))
+    }
+
+    #[test]
+    fn menu_offers_pause_while_playing() {
+        let playing = MediaInfo {
+            status: PlayerStatus::Playing,
+            ..Default::default()
+        };
+        assert_eq!(
+            label(MenuItem::PlayPause, &playing).as_deref(),
+            Some("Pause")
+        );
+        let paused = MediaInfo::default();
+        assert_eq!(label(MenuItem::PlayPause, &paused).as_deref(), Some("Play"));
+
+        let (_, _, children) = layout(0, &paused).unwrap();
+        assert_eq!(children.len(), MenuItem::ALL.len());
+        assert!(layout(99, &paused).is_none());
+    }
+
+    #[test]
+    fn menu_leads_with_the_track() {
+        let info = MediaInfo {
+            title: "snake_case".to_string(),
+            artist: "Artist".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            label(MenuItem::Track, &info).as_deref(),
+            Some("snake__case — Artist")
+        );
+        assert_eq!(MenuItem::from_name(" Prev"), Some(MenuItem::Previous));
+        assert_eq!(MenuItem::from_name("title"), None);
+    }
+}
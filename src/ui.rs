@@ -1,15 +1,68 @@
 use adw::prelude::*;
 use gtk::glib;
 use gtk::prelude::*;
-use gtk::StringObject;
 use libadwaita as adw;
 use std::collections::VecDeque;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use crate::mpris_client::{MediaInfo, MprisClient, PlayerStatus};
+use crate::art_cache;
+use crate::autostart;
+use crate::config::{
+    BackgroundClickAction, Config, Control, PlayerIdentityCaption, DEFAULT_ART_SIZE,
+};
+use crate::listen_log::ListenLog;
+use crate::mpris_client::{
+    art_local_path, AvailablePlayer, CommandError, CommandKind, CommandResult, LoopMode, MediaInfo,
+    MprisClient, PlayerStatus, QueuedTrack, GAPLESS_LABEL,
+};
+use crate::play_history::{PlayHistory, PlayedTrack};
+use crate::player_entry::PlayerEntry;
 use crate::progress_ring_button::ProgressRingButton;
+use crate::session_watch;
+use crate::share_card;
+use crate::state::State;
+use crate::status_file::StatusFile;
+#[cfg(feature = "tray")]
+use crate::tray::{self, TrayEvent};
+use crate::util::format_time;
+use crate::window_icon;
+use crate::APP_ID;
+
+// Touchpad scroll distance, in surface pixels, that counts as one seek step.
+const SCROLL_SURFACE_STEP: f64 = 40.0;
+const FADE_IN_MS: u32 = 180;
+// Widest the metadata labels ask to be, in characters. An ellipsized label
+// otherwise asks for its full text width, so one long album name can widen
+// the window or squeeze the labels next to it.
+const LABEL_MAX_WIDTH_CHARS: i32 = 28;
+// The single compact line holds title, artist and album.
+const COMPACT_LABEL_MAX_WIDTH_CHARS: i32 = 40;
+// The sidebar propagates its natural width, so its labels need a cap too.
+const SIDEBAR_LABEL_MAX_WIDTH_CHARS: i32 = 22;
+// How far the pointer has to move on the art before it drags the window.
+const WINDOW_DRAG_THRESHOLD: f64 = 4.0;
+// Give up on art that takes longer than this, rather than waiting on a
+// stalled server for reqwest's default 30 seconds
+const ART_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(10);
+// Number of stars a rating is shown out of.
+const RATING_STARS: u32 = 5;
+// Speeds offered by the playback speed control.
+const PLAYBACK_RATES: &[f64] = &[0.75, 1.0, 1.25, 1.5, 2.0];
+// How long a setting has to stay put before it's written to the config file.
+const CONFIG_SAVE_DELAY: Duration = Duration::from_millis(500);
+// Longest side the backdrop art is shrunk to before blurring.
+const BACKDROP_SIZE: usize = 24;
+// Gap between the pointer and the top of the floating controls.
+const FLOATING_CONTROLS_OFFSET: f64 = 16.0;
+// How near the pointer can get to the floating controls before they stop
+// following it.
+const FLOATING_CONTROLS_SLACK: f64 = 24.0;
+// Name of the view stack page that lists every player.
+const ALL_PLAYERS_PAGE: &str = "players";
+// Size of the art or player icon on each all-players card.
+const PLAYER_CARD_ART_SIZE: i32 = 48;
 
 #[derive(Clone)]
 struct StatusHistoryEntry {
@@ -23,36 +76,80 @@ struct StatusHistoryEntry {
 struct SidebarContent {
     container: gtk::Box,
     list_box: gtk::ListBox,
+    details_box: gtk::Box,
+    details_list: gtk::ListBox,
 }
 
-pub fn build_ui(app: &adw::Application) -> adw::ApplicationWindow {
+/// `startup_player` is a player to control from the start, given on the
+/// command line, which isn't remembered for next time.
+pub fn build_ui(
+    app: &adw::Application,
+    config: &Config,
+    startup_player: Option<String>,
+) -> adw::ApplicationWindow {
     let window = adw::ApplicationWindow::builder()
         .application(app)
         .title("Empress")
         .default_width(320)
         .default_height(400)
+        .hide_on_close(config.hide_on_close)
         .build();
 
     window.set_icon_name(None);
 
-    window.set_size_request(150, 150);
+    // Grow the minimum size along with art larger than the default, so the
+    // window never gets narrower than the album art it has to show
+    let min_size = 150 + (config.art_size() - DEFAULT_ART_SIZE).max(0);
+    window.set_size_request(min_size, min_size);
+
+    let state = State::load();
+    if let (Some(width), Some(height)) = (state.window_width, state.window_height) {
+        window.set_default_size(width.max(min_size), height.max(min_size));
+    }
+    window.connect_close_request(|window| {
+        save_window_size(window.upcast_ref());
+        glib::Propagation::Proceed
+    });
 
     let header_bar = adw::HeaderBar::new();
-    header_bar.set_show_title(false);
-    header_bar.set_title_widget(None::<&gtk::Widget>);
+
+    // The selected player, or a card for every running player
+    let view_stack = adw::ViewStack::new();
+    header_bar.set_title_widget(Some(
+        &adw::ViewSwitcher::builder()
+            .stack(&view_stack)
+            .policy(adw::ViewSwitcherPolicy::Narrow)
+            .build(),
+    ));
 
     // Create combo box for player selection
-    let player_list = gtk::StringList::new(&[]);
+    let player_list = gio::ListStore::new::<PlayerEntry>();
     let player_combo = gtk::DropDown::builder()
         .model(&player_list)
+        .factory(&player_entry_factory())
         .tooltip_text("Select MPRIS player")
         .build();
 
     // Add "Auto" option as default
-    player_list.append("Auto");
+    player_list.append(&PlayerEntry::new("Auto", FALLBACK_PLAYER_ICON));
 
     let sidebar = build_sidebar();
-    let content = build_content();
+    let content = build_content(config);
+
+    if config.art_as_window_icon {
+        let window = window.downgrade();
+        content.album_art.connect_paintable_notify(move |picture| {
+            let Some(window) = window.upgrade() else {
+                return;
+            };
+            let art = picture
+                .paintable()
+                .and_then(|p| p.downcast::<gdk::Texture>().ok());
+            if let Err(e) = window_icon::set_from_art(window.upcast_ref(), art.as_ref()) {
+                eprintln!("Failed to set the window icon from album art: {}", e);
+            }
+        });
+    }
 
     player_combo.set_halign(gtk::Align::Center);
     player_combo.set_margin_top(6);
@@ -76,11 +173,64 @@ pub fn build_ui(app: &adw::Application) -> adw::ApplicationWindow {
         }
     });
 
-    // Add drag gesture to move window on the album art area only
+    // Recently played tracks
+    let play_history = Arc::new(Mutex::new(PlayHistory::load(config.persist_play_history)));
+    let history_list = gtk::ListBox::builder()
+        .css_classes(vec!["boxed-list"])
+        .selection_mode(gtk::SelectionMode::None)
+        .build();
+    history_list.set_placeholder(Some(
+        &gtk::Label::builder()
+            .label("Nothing played yet")
+            .margin_top(12)
+            .margin_bottom(12)
+            .opacity(0.7)
+            .build(),
+    ));
+    let history_popover = gtk::Popover::builder()
+        .child(
+            &gtk::ScrolledWindow::builder()
+                .child(&history_list)
+                .hscrollbar_policy(gtk::PolicyType::Never)
+                .propagate_natural_height(true)
+                .max_content_height(360)
+                .width_request(260)
+                .build(),
+        )
+        .build();
+    let history_button = gtk::MenuButton::builder()
+        .icon_name("document-open-recent-symbolic")
+        .tooltip_text("Recently Played")
+        .popover(&history_popover)
+        .css_classes(vec!["flat"])
+        .build();
+    header_bar.pack_end(&history_button);
+    if let Ok(history) = play_history.lock() {
+        update_play_history(&history_list, history.tracks());
+    }
+
+    // Main menu
+    let menu_button = gtk::MenuButton::builder()
+        .icon_name("open-menu-symbolic")
+        .tooltip_text("Main Menu")
+        .menu_model(&build_main_menu())
+        .css_classes(vec!["flat"])
+        .build();
+    header_bar.pack_end(&menu_button);
+
+    // Add drag gesture to move window on the album art area only. The move
+    // starts once the pointer has actually moved, so clicks on the art
+    // still get through.
     let drag_gesture = gtk::GestureDrag::new();
-    drag_gesture.connect_drag_begin({
+    let moving = Arc::new(AtomicBool::new(false));
+    drag_gesture.connect_drag_update({
         let window = window.clone();
-        move |gesture, _x, _y| {
+        let moving = moving.clone();
+        move |gesture, offset_x, offset_y| {
+            if moving.load(Ordering::SeqCst) || offset_x.hypot(offset_y) < WINDOW_DRAG_THRESHOLD {
+                return;
+            }
+            moving.store(true, Ordering::SeqCst);
             if let Some(device) = gesture.device() {
                 if let Some(surface) = window.surface() {
                     if let Ok(toplevel) = surface.downcast::<gtk::gdk::Toplevel>() {
@@ -90,6 +240,7 @@ pub fn build_ui(app: &adw::Application) -> adw::ApplicationWindow {
             }
         }
     });
+    drag_gesture.connect_drag_end(move |_, _, _| moving.store(false, Ordering::SeqCst));
     content.art_container.add_controller(drag_gesture);
 
     let main_box = gtk::Box::builder()
@@ -112,13 +263,104 @@ pub fn build_ui(app: &adw::Application) -> adw::ApplicationWindow {
     // Initially hide sidebar
     sidebar.container.set_visible(false);
 
-    toolbar_view.set_content(Some(&paned));
+    // Toasts for transient errors, e.g. a seek the player rejected
+    let toast_overlay = adw::ToastOverlay::new();
+    toast_overlay.set_child(Some(&view_stack));
+
+    view_stack.add_titled_with_icon(
+        &paned,
+        Some("player"),
+        "Now Playing",
+        "media-playback-start-symbolic",
+    );
+    let all_players_list = gtk::ListBox::builder()
+        .css_classes(vec!["boxed-list"])
+        .selection_mode(gtk::SelectionMode::None)
+        .valign(gtk::Align::Start)
+        .build();
+    all_players_list.set_placeholder(Some(
+        &gtk::Label::builder()
+            .label("No media players running")
+            .margin_top(12)
+            .margin_bottom(12)
+            .opacity(0.7)
+            .build(),
+    ));
+    view_stack.add_titled_with_icon(
+        &gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .child(
+                &adw::Clamp::builder()
+                    .child(&all_players_list)
+                    .margin_top(12)
+                    .margin_bottom(12)
+                    .margin_start(12)
+                    .margin_end(12)
+                    .build(),
+            )
+            .build(),
+        Some(ALL_PLAYERS_PAGE),
+        "All Players",
+        "view-list-symbolic",
+    );
+
+    toolbar_view.set_content(Some(&toast_overlay));
+
+    // Copy a recently played track, e.g. to look it up elsewhere
+    history_list.connect_row_activated({
+        let play_history = play_history.clone();
+        let toast_overlay = toast_overlay.downgrade();
+        move |list, row| {
+            let Ok(history) = play_history.lock() else {
+                return;
+            };
+            let Some(track) = history.tracks().get(row.index() as usize) else {
+                return;
+            };
+            let text = if track.artist.is_empty() {
+                track.title.clone()
+            } else {
+                format!("{} - {}", track.artist, track.title)
+            };
+            list.clipboard().set_text(&text);
+            if let Some(toast_overlay) = toast_overlay.upgrade() {
+                toast_overlay.add_toast(adw::Toast::new("Copied to clipboard"));
+            }
+        }
+    });
     main_box.append(&toolbar_view);
-    window.set_content(Some(&main_box));
+    // The backdrop fills the window without affecting its size. It's
+    // always there, just hidden, so the preferences can turn it on.
+    let overlay = gtk::Overlay::new();
+    overlay.add_overlay(&content.backdrop);
+    overlay.add_overlay(&main_box);
+    overlay.set_measure_overlay(&main_box, true);
+    window.set_content(Some(&overlay));
+
+    // Settings the preferences can change while running
+    let live_config = Arc::new(Mutex::new(config.clone()));
 
     let mpris_client = MprisClient::new();
+    if startup_player.is_some() {
+        mpris_client.set_preferred_player(startup_player.clone());
+    }
+    mpris_client.set_default_volume(config.default_volume);
+    mpris_client.set_art_metadata_keys(config.art_metadata_keys.clone());
+    mpris_client.set_player_priority(config.player_priority.clone());
+    mpris_client.set_pause_fade(Duration::from_millis(config.pause_fade_ms));
+    mpris_client.set_status_file(
+        config
+            .status_file
+            .clone()
+            .map(|path| StatusFile::new(path, config.status_file_format.clone())),
+    );
+    mpris_client.set_listen_log(config.listen_log.clone().map(ListenLog::new));
+    mpris_client.set_fetch_queue(config.show_playlist_remaining);
     let monitor_tick = mpris_client.take_monitor_tick().expect("monitor tick not taken");
     let media_receiver = mpris_client.start_monitoring(monitor_tick);
+    let command_results = mpris_client
+        .take_command_results()
+        .expect("command results not taken");
 
     // Set up player combo box functionality
     let player_list_clone = player_list.clone();
@@ -130,6 +372,8 @@ pub fn build_ui(app: &adw::Application) -> adw::ApplicationWindow {
     let is_refreshing_for_refresh = is_refreshing.clone();
     let is_refreshing_for_handler = is_refreshing.clone();
 
+    let player_count_for_refresh = content.player_count.clone();
+
     // Refresh player list every 5 seconds
     glib::timeout_add_local(Duration::from_secs(5), move || {
         // Block the selection handler while we repopulate the model
@@ -145,8 +389,9 @@ pub fn build_ui(app: &adw::Application) -> adw::ApplicationWindow {
 
         let available = MprisClient::get_available_players();
         for player in &available {
-            player_list_clone.append(player);
+            player_list_clone.append(&player_entry(player));
         }
+        player_count_for_refresh.store(available.len(), Ordering::SeqCst);
 
         // Restore selection if possible
         if current_selected < player_list_clone.n_items() {
@@ -162,43 +407,74 @@ pub fn build_ui(app: &adw::Application) -> adw::ApplicationWindow {
     {
         let available = MprisClient::get_available_players();
         for player in &available {
-            player_list.append(player);
+            player_list.append(&player_entry(player));
         }
+        content
+            .player_count
+            .store(available.len(), Ordering::SeqCst);
     }
 
     // Handle player selection changes
     player_combo.connect_selected_item_notify({
         let mpris_client = mpris_client_for_combo.clone();
+        let content = content.clone();
         move |combo| {
             // Skip during combo refresh to avoid resetting preferred player
             if is_refreshing_for_handler.load(Ordering::SeqCst) {
                 return;
             }
+            // Clear the old player's progress right away; the new player's
+            // arrives with the next update
+            reset_progress(&content);
             let selected = combo.selected();
-            if selected == 0 {
+            let player_name = if selected == 0 {
                 // "Auto" selected - clear preferred player
-                mpris_client.set_preferred_player(None);
+                None
             } else {
                 // Specific player selected
-                if let Some(item) = combo.selected_item() {
-                    if let Some(str_obj) = item.downcast_ref::<StringObject>() {
-                        let player_name = str_obj.string().to_string();
-                        mpris_client.set_preferred_player(Some(player_name));
-                    }
-                }
-            }
+                combo
+                    .selected_item()
+                    .and_then(|item| item.downcast::<PlayerEntry>().ok())
+                    .map(|entry| entry.identity())
+            };
+            mpris_client.set_preferred_player(player_name.clone());
+            // Picked again on the next launch, if it's running then
+            State::update(|state| state.preferred_player = player_name);
         }
     });
 
-    let title_label = content.title_label.downgrade();
-    let artist_label = content.artist_label.downgrade();
-    let album_label = content.album_label.downgrade();
-    let album_art = content.album_art.downgrade();
-    let placeholder_label = content.placeholder_label.downgrade();
-    let art_container = content.art_container.downgrade();
-    let play_pause_button = content.play_pause_button.downgrade();
-    let volume_scale = content.volume_scale.downgrade();
-    let volume_clamp = content.volume_clamp.downgrade();
+    // A player from the command line is shown as picked but not saved
+    let (wanted, save) = match startup_player {
+        Some(player) => (Some(player), false),
+        None => (state.preferred_player.clone(), true),
+    };
+    if let Some(ref wanted) = wanted {
+        let position = (1..player_list.n_items()).find(|&i| {
+            player_list
+                .item(i)
+                .and_downcast::<PlayerEntry>()
+                .is_some_and(|entry| entry.identity().eq_ignore_ascii_case(wanted))
+        });
+        if let Some(position) = position {
+            is_refreshing.store(!save, Ordering::SeqCst);
+            player_combo.set_selected(position);
+            is_refreshing.store(false, Ordering::SeqCst);
+        }
+    }
+
+    // Set while the volume slider is synced from the player, so that
+    // programmatic updates aren't sent back as user volume changes
+    let volume_updating = Arc::new(AtomicBool::new(false));
+    let volume_updating_for_updates = volume_updating.clone();
+
+    // Whether the current player supports seeking; scroll-seek is disabled
+    // when it doesn't rather than sending commands that will be refused
+    let can_seek = Arc::new(AtomicBool::new(false));
+    let can_seek_for_updates = can_seek.clone();
+
+    // Most recent info from the monitor, for actions that need it on demand
+    let latest_info = Arc::new(Mutex::new(MediaInfo::default()));
+    let latest_info_for_updates = latest_info.clone();
 
     // Track last known art URL to detect changes
     let last_art_url = Arc::new(Mutex::new(None::<String>));
@@ -216,150 +492,373 @@ pub fn build_ui(app: &adw::Application) -> adw::ApplicationWindow {
 
     // Sidebar references for updates
     let sidebar_list_box = sidebar.list_box.clone();
+    let sidebar_for_updates = sidebar.clone();
+    let play_history_for_updates = play_history.clone();
+    let history_list_for_updates = history_list.clone();
+    let last_extra_properties = Arc::new(Mutex::new(Vec::<(&'static str, String)>::new()));
     let history_for_updates = history.clone();
     let last_status_for_updates = last_status.clone();
     let last_title_for_updates = last_title.clone();
     let last_artist_for_updates = last_artist.clone();
 
-    // Poll the receiver from the main GTK thread
+    // Poll the receiver from the main GTK thread, until the window goes away
+    let window_for_updates = window.downgrade();
+    let content_for_updates = content.clone();
+    let mpris_client_for_updates = mpris_client.clone();
+    let position_in_title = config.position_in_title;
+    let live_config_for_updates = live_config.clone();
+    let show_playlist_remaining = config.show_playlist_remaining;
+    let art_debounce = Duration::from_millis(config.art_debounce_ms);
+    let mut art_candidate: Option<(Option<String>, Instant)> = None;
+    // Last info fully rendered, and the player count it was rendered with
+    let mut last_rendered: Option<(MediaInfo, usize)> = None;
+    let mut last_rendered_at = Instant::now();
+    let mut last_window_title_update: Option<Instant> = None;
     glib::timeout_add_local(std::time::Duration::from_millis(500), move || {
+        let Some(window) = window_for_updates.upgrade() else {
+            return glib::ControlFlow::Break;
+        };
+        let content = &content_for_updates;
+
         // Process all available messages
-        while let Ok(info) = media_receiver.try_recv() {
-            let title_label = title_label.upgrade();
-            let artist_label = artist_label.upgrade();
-            let album_label = album_label.upgrade();
-            let album_art = album_art.upgrade();
-            let placeholder_label = placeholder_label.upgrade();
-            let art_container = art_container.upgrade();
-            let play_pause_button = play_pause_button.upgrade();
-            let volume_scale = volume_scale.upgrade();
-            let volume_clamp = volume_clamp.upgrade();
-
-            if let (
-                Some(title_label),
-                Some(artist_label),
-                Some(album_label),
-                Some(album_art),
-                Some(placeholder_label),
-                Some(art_container),
-                Some(play_pause_button),
-                Some(volume_scale),
-                Some(volume_clamp),
-            ) = (
-                title_label,
-                artist_label,
-                album_label,
-                album_art,
-                placeholder_label,
-                art_container,
-                play_pause_button,
-                volume_scale,
-                volume_clamp,
-            ) {
-                let title_changed = if let Ok(last) = last_title_for_updates.lock() {
-                    *last != info.title
-                } else {
-                    true
-                };
+        let updates: Vec<MediaInfo> = media_receiver.try_iter().collect();
+        if updates.is_empty() {
+            // The monitor only sends when something changes, so keep the
+            // position moving and put up art that has waited out the debounce
+            if let Some((info, _)) = &last_rendered {
+                let info = info.advanced_by(last_rendered_at.elapsed());
+                if info.status == PlayerStatus::Playing {
+                    update_progress(content, &info);
+                    let due = last_window_title_update
+                        .is_none_or(|last| last.elapsed() >= Duration::from_secs(1));
+                    if position_in_title && due {
+                        window.set_title(Some(&window_title_with_position(&info)));
+                        last_window_title_update = Some(Instant::now());
+                    }
+                    if let Ok(mut latest) = latest_info_for_updates.lock() {
+                        latest.position = info.position;
+                    }
+                }
 
-                let artist_changed = if let Ok(last) = last_artist_for_updates.lock() {
-                    *last != info.artist
-                } else {
-                    true
-                };
+                let art_settled = art_candidate.as_ref().is_some_and(|(url, since)| {
+                    since.elapsed() >= art_debounce
+                        && last_art_url_for_updates
+                            .lock()
+                            .is_ok_and(|last| *last != *url)
+                });
+                if art_settled {
+                    update_ui_widgets(content, &info, true);
+                    if let Ok(mut last_url) = last_art_url_for_updates.lock() {
+                        *last_url = info.art_url.clone();
+                    }
+                }
+            }
+        }
+        for info in updates {
+            // Read from a player that has since been switched away from
+            if info.selection_generation != mpris_client_for_updates.selection_generation() {
+                continue;
+            }
 
-                let url_changed = if let Ok(last_url) = last_art_url_for_updates.lock() {
-                    last_url.as_ref() != info.art_url.as_ref()
-                } else {
-                    true
+            // Leave the last track's art in place and only swap the text,
+            // without recording anything in the history
+            if info.reconnecting {
+                let placeholder = MediaInfo {
+                    title: "Reconnecting…".to_string(),
+                    ..Default::default()
                 };
+                update_ui_widgets(content, &placeholder, false);
+                last_rendered = None;
+                continue;
+            }
 
-                let is_initial = if let Ok(initial) = initial_load_done.lock() {
-                    !*initial
-                } else {
-                    true
-                };
+            if let Ok(mut history) = play_history_for_updates.lock() {
+                if history.record(&info) {
+                    update_play_history(&history_list_for_updates, history.tracks());
+                }
+            }
 
-                let force_art_update = is_initial || url_changed || title_changed || artist_changed;
-
-                update_ui_widgets(
-                    &title_label,
-                    &artist_label,
-                    &album_label,
-                    &album_art,
-                    &placeholder_label,
-                    &art_container,
-                    &play_pause_button,
-                    &info,
-                    force_art_update,
-                );
-
-                let controllable = info.can_control && info.volume.is_some();
-                volume_clamp.set_visible(controllable);
-                if controllable {
-                    if let Some(v) = info.volume {
-                        let clamped = v.max(0.0).min(1.0);
-                        volume_scale.set_value(clamped);
-                    }
+            let mut details = info.extra_properties.clone();
+            details.extend(info.loudness.iter().cloned());
+            if show_playlist_remaining {
+                if let Some(remaining) = playlist_remaining(&info) {
+                    details.push(("Left in Playlist", format_minutes(remaining)));
                 }
+            }
+            if let Ok(mut last) = last_extra_properties.lock() {
+                if *last != details {
+                    update_player_details(&sidebar_for_updates, &details);
+                    *last = details;
+                }
+            }
 
-                if url_changed || title_changed || artist_changed {
-                    if let Ok(mut last_url) = last_art_url_for_updates.lock() {
-                        *last_url = info.art_url.clone();
+            let title_changed = if let Ok(last) = last_title_for_updates.lock() {
+                *last != info.title
+            } else {
+                true
+            };
+
+            let artist_changed = if let Ok(last) = last_artist_for_updates.lock() {
+                *last != info.artist
+            } else {
+                true
+            };
+
+            let url_changed = if let Ok(last_url) = last_art_url_for_updates.lock() {
+                last_url.as_ref() != info.art_url.as_ref()
+            } else {
+                true
+            };
+
+            let is_initial = if let Ok(initial) = initial_load_done.lock() {
+                !*initial
+            } else {
+                true
+            };
+
+            // Not for the track that was already playing at startup, or
+            // while the user is looking at the window anyway
+            let track_notifications = live_config_for_updates
+                .lock()
+                .is_ok_and(|config| config.track_notifications);
+            if track_notifications
+                && (title_changed || artist_changed)
+                && !is_initial
+                && !window.is_active()
+            {
+                if let Some(app) = window.application() {
+                    notify_track_change(&app, &info);
+                    let art_missing = info.art_url.as_deref().is_some_and(|url| {
+                        is_web_url(url) && art_cache::cached_path(url).is_none()
+                    });
+                    if let Ok(mut notified) = content.notified_without_art.lock() {
+                        *notified = art_missing.then(|| info.clone());
                     }
                 }
+            }
+
+            // Restart the wait whenever the URL differs from the last one seen
+            if art_candidate.as_ref().map(|(url, _)| url) != Some(&info.art_url) {
+                art_candidate = Some((info.art_url.clone(), Instant::now()));
+            }
+            let art_settled = art_candidate
+                .as_ref()
+                .is_none_or(|(_, since)| since.elapsed() >= art_debounce);
+
+            let force_art_update =
+                is_initial || ((url_changed || title_changed || artist_changed) && art_settled);
+
+            // Skip the full refresh when only the position moved
+            let player_count = content.player_count.load(Ordering::SeqCst);
+            let unchanged = !force_art_update
+                && last_rendered.as_ref().is_some_and(|(last, count)| {
+                    *count == player_count && last.same_except_progress(&info)
+                });
+            if unchanged {
+                update_progress(content, &info);
+            } else {
+                update_ui_widgets(content, &info, force_art_update);
+            }
+            last_rendered = Some((info.clone(), player_count));
+            last_rendered_at = Instant::now();
+
+            // At most once a second, except on track changes, since some
+            // taskbars redraw on every title change
+            if position_in_title {
+                let due = last_window_title_update
+                    .is_none_or(|last| last.elapsed() >= Duration::from_secs(1));
+                if due || title_changed || artist_changed {
+                    window.set_title(Some(&window_title_with_position(&info)));
+                    last_window_title_update = Some(Instant::now());
+                }
+            }
+
+            can_seek_for_updates.store(info.can_seek, Ordering::SeqCst);
+
+            if let Some(action) = window
+                .lookup_action("stop-after-current")
+                .and_then(|a| a.downcast::<gio::SimpleAction>().ok())
+            {
+                if action.state() != Some(info.stop_after_current.to_variant()) {
+                    action.set_state(&info.stop_after_current.to_variant());
+                }
+            }
+
+            if let Ok(mut latest) = latest_info_for_updates.lock() {
+                *latest = info.clone();
+            }
+
+            let controllable = info.can_control && info.volume.is_some();
+            content
+                .volume_clamp
+                .set_visible(controllable && content.show_volume);
+            if controllable {
+                if let Some(v) = info.volume {
+                    let clamped = v.max(0.0).min(1.0);
+                    volume_updating_for_updates.store(true, Ordering::SeqCst);
+                    content.volume_scale.set_value(clamped);
+                    volume_updating_for_updates.store(false, Ordering::SeqCst);
+                    update_mute_button(content, clamped);
+                }
+            }
+
+            if force_art_update {
+                if let Ok(mut last_url) = last_art_url_for_updates.lock() {
+                    *last_url = info.art_url.clone();
+                }
+            }
+
+            if is_initial {
+                if let Ok(mut initial) = initial_load_done.lock() {
+                    *initial = true;
+                }
+            }
+
+            let status_changed = if let Ok(last) = last_status_for_updates.lock() {
+                *last != info.status
+            } else {
+                true
+            };
+
+            if status_changed || title_changed || artist_changed {
+                if let Ok(mut last) = last_status_for_updates.lock() {
+                    *last = info.status.clone();
+                }
+                if let Ok(mut last) = last_title_for_updates.lock() {
+                    *last = info.title.clone();
+                }
+                if let Ok(mut last) = last_artist_for_updates.lock() {
+                    *last = info.artist.clone();
+                }
+
+                if let Ok(mut history) = history_for_updates.lock() {
+                    let entry = StatusHistoryEntry {
+                        status: info.status.clone(),
+                        title: info.title.clone(),
+                        artist: info.artist.clone(),
+                        timestamp: Instant::now(),
+                    };
+                    history.push_front(entry);
 
-                if is_initial {
-                    if let Ok(mut initial) = initial_load_done.lock() {
-                        *initial = true;
+                    while history.len() > 50 {
+                        history.pop_back();
                     }
+
+                    update_sidebar(&sidebar_list_box, &history);
                 }
+            }
+        }
+        glib::ControlFlow::Continue
+    });
 
-                let status_changed = if let Ok(last) = last_status_for_updates.lock() {
-                    *last != info.status
-                } else {
-                    true
+    // Surface commands that weren't carried out
+    glib::timeout_add_local(Duration::from_millis(250), {
+        let toast_overlay = toast_overlay.downgrade();
+        let can_seek = can_seek.clone();
+        move || {
+            let Some(toast_overlay) = toast_overlay.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            while let Ok(CommandResult { kind, result }) = command_results.try_recv() {
+                let error = match result {
+                    // A seek that went through means seeking works again
+                    Ok(()) if matches!(kind, CommandKind::Seek | CommandKind::SetPosition) => {
+                        can_seek.store(true, Ordering::SeqCst);
+                        continue;
+                    }
+                    Ok(()) => continue,
+                    Err(error) => error,
                 };
-
-                if status_changed || title_changed || artist_changed {
-                    if let Ok(mut last) = last_status_for_updates.lock() {
-                        *last = info.status.clone();
+                match (kind, error) {
+                    // Disable the control until the next update says otherwise
+                    (
+                        CommandKind::Seek | CommandKind::SetPosition,
+                        CommandError::Unsupported(_),
+                    ) => {
+                        can_seek.store(false, Ordering::SeqCst);
                     }
-                    if let Ok(mut last) = last_title_for_updates.lock() {
-                        *last = info.title.clone();
+                    (CommandKind::Raise, CommandError::Unsupported(_)) => {
+                        toast_overlay.add_toast(adw::Toast::new("This player can't be raised"));
                     }
-                    if let Ok(mut last) = last_artist_for_updates.lock() {
-                        *last = info.artist.clone();
+                    (CommandKind::QuitPlayer, CommandError::Unsupported(_)) => {
+                        toast_overlay.add_toast(adw::Toast::new("This player can't be quit"));
                     }
-
-                    if let Ok(mut history) = history_for_updates.lock() {
-                        let entry = StatusHistoryEntry {
-                            status: info.status.clone(),
-                            title: info.title.clone(),
-                            artist: info.artist.clone(),
-                            timestamp: Instant::now(),
-                        };
-                        history.push_front(entry);
-
-                        while history.len() > 50 {
-                            history.pop_back();
-                        }
-
-                        update_sidebar(&sidebar_list_box, &history);
+                    // Some players reject jumps outright; playback just
+                    // stays where it is
+                    (CommandKind::SetPosition, CommandError::Failed(..)) => {}
+                    (_, CommandError::Unsupported(action)) => {
+                        eprintln!("Player does not support {}", action);
+                    }
+                    (_, CommandError::NoPlayer(action)) => {
+                        eprintln!("No player to {}", action);
+                        toast_overlay.add_toast(adw::Toast::new("No media player is running"));
+                    }
+                    (_, CommandError::Failed(action, e)) => {
+                        eprintln!("Failed to {}: {}", action, e);
+                        toast_overlay.add_toast(adw::Toast::new(&format!("Couldn't {}", action)));
                     }
                 }
             }
+            glib::ControlFlow::Continue
         }
-        glib::ControlFlow::Continue
     });
 
-    setup_controls(&content, mpris_client.clone());
-    setup_keyboard_shortcuts(&window, mpris_client);
+    setup_controls(
+        &content,
+        mpris_client.clone(),
+        volume_updating,
+        can_seek,
+        live_config.clone(),
+    );
+    setup_background_click(&content, mpris_client.clone(), config.background_click);
+    setup_art_double_click(&content, mpris_client.clone());
+    setup_position_editing(&content, mpris_client.clone(), latest_info.clone());
+    setup_ring_seek(&content, mpris_client.clone(), latest_info.clone());
+    setup_all_players(&view_stack, &all_players_list, mpris_client.clone());
+    setup_seek_bar(&content, mpris_client.clone());
+    if config.pause_on_lock {
+        session_watch::spawn(
+            mpris_client.clone(),
+            latest_info.clone(),
+            config.resume_on_unlock,
+        );
+    }
+    setup_notification_actions(app, mpris_client.clone());
+    #[cfg(feature = "tray")]
+    setup_tray(
+        &window,
+        mpris_client.clone(),
+        latest_info.clone(),
+        &config.tray_raise_on,
+    );
+    setup_actions(
+        &window,
+        &content,
+        mpris_client.clone(),
+        latest_info.clone(),
+        config,
+    );
+    setup_preferences(&window, &content, mpris_client.clone(), live_config);
+    setup_keyboard_shortcuts(&window, mpris_client, latest_info);
 
-    // Set play/pause button as the default focus
+    // Fade in and focus play/pause once the window is mapped. Grabbing
+    // focus from `show` can happen before the compositor maps the window,
+    // which loses it and leaves the shortcuts dead until the first click.
+    let fade_in = adw::TimedAnimation::new(
+        &main_box,
+        0.0,
+        1.0,
+        FADE_IN_MS,
+        adw::PropertyAnimationTarget::new(&main_box, "opacity"),
+    );
+    fade_in.set_easing(adw::Easing::EaseOutCubic);
     let play_pause_button = content.play_pause_button.clone();
-    window.connect_show(move |_| {
-        play_pause_button.grab_focus();
+    window.connect_map(move |_| {
+        fade_in.play();
+        let play_pause_button = play_pause_button.clone();
+        glib::idle_add_local_once(move || {
+            play_pause_button.grab_focus();
+        });
     });
 
     window
@@ -371,22 +870,82 @@ struct MediaContent {
     content_column: gtk::Box,
     clamp: adw::Clamp,
     album_art: gtk::Picture,
+    placeholder: gtk::Box,
+    // Behind the whole window with `art_backdrop`
+    backdrop: gtk::Picture,
     placeholder_label: gtk::Label,
+    placeholder_icon: gtk::Image,
     art_container: gtk::Box,
     title_label: gtk::Label,
     artist_label: gtk::Label,
     album_label: gtk::Label,
+    rating_box: gtk::Box,
+    // Whether clicking a star rates the track
+    can_rate: Arc<AtomicBool>,
+    identity_label: gtk::Label,
+    gapless_label: gtk::Label,
+    stop_after_label: gtk::Label,
+    position_stack: gtk::Stack,
+    position_button: gtk::Button,
+    seek_row: gtk::Box,
+    seek_scale: gtk::Scale,
+    seek_elapsed_label: gtk::Label,
+    seek_length_label: gtk::Label,
+    // Set while the user is dragging the seek bar, so updates from the
+    // player don't yank it back mid-scrub
+    seek_dragging: Arc<AtomicBool>,
+    position_entry: gtk::Entry,
+    identity_caption: PlayerIdentityCaption,
+    // Number of running players, as of the last player list refresh
+    player_count: Arc<AtomicUsize>,
     play_pause_button: ProgressRingButton,
+    compact_metadata: bool,
     prev_button: gtk::Button,
     next_button: gtk::Button,
+    stop_button: gtk::Button,
+    restart_button: gtk::Button,
+    shuffle_button: gtk::ToggleButton,
+    repeat_button: gtk::Button,
+    rate_dropdown: gtk::DropDown,
+    rate_list: gtk::StringList,
+    // Rates offered by the dropdown, in order: those in `PLAYBACK_RATES`
+    // that the current player accepts
+    rates: Arc<Mutex<Vec<f64>>>,
+    // Set while shuffle is synced from the player, so the toggle doesn't
+    // send it back
+    controls_updating: Arc<AtomicBool>,
+    // Loop mode from the latest update, for the repeat button to step from
+    loop_mode: Arc<Mutex<Option<LoopMode>>>,
+    show_volume: bool,
     volume_scale: gtk::Scale,
+    mute_button: gtk::Button,
     volume_clamp: adw::Clamp,
+    queue_expander: gtk::Expander,
+    queue_list: gtk::StringList,
+    queue_view: gtk::ListView,
+    // Tracks shown in the queue, for their ids when one is picked
+    queue: Arc<Mutex<Vec<QueuedTrack>>>,
+    // Art URL the latest forced update asked for
+    pending_art_url: Arc<Mutex<Option<String>>>,
+    // Track last notified about before its web art was downloaded, to
+    // notify again with the art once it's in
+    notified_without_art: Arc<Mutex<Option<MediaInfo>>>,
 }
 
-fn build_content() -> MediaContent {
+fn build_content(config: &Config) -> MediaContent {
+    let art_size = config.art_size();
+    // Widened when the art is configured larger than the default allows
+    let content_width = (art_size + 24).max(280);
+    // Larger text needs more room before it ellipsizes
+    let content_width = if config.accessible_mode {
+        content_width + 80
+    } else {
+        content_width
+    };
+
     // Main container using Clamp for content width following HIG
     let clamp = adw::Clamp::builder()
-        .maximum_size(280)
+        .maximum_size(content_width)
         .tightening_threshold(200)
         .build();
 
@@ -397,7 +956,9 @@ fn build_content() -> MediaContent {
         .margin_bottom(0)
         .margin_start(12)
         .margin_end(12)
-        .valign(gtk::Align::Fill)
+        // Centered rather than top-packed so the art and text stay balanced
+        // above the controls however many metadata lines are showing
+        .valign(gtk::Align::Center)
         .halign(gtk::Align::Fill)
         .vexpand(true)
         .hexpand(true)
@@ -419,22 +980,50 @@ fn build_content() -> MediaContent {
         .content_fit(gtk::ContentFit::Cover)
         .vexpand(true)
         .hexpand(true)
-        .width_request(180)
-        .height_request(180)
+        .width_request(art_size)
+        .height_request(art_size)
         .css_classes(vec!["album-art"])
         .build();
 
+    // Shows a blurred copy of the art, made once per cover by `set_art`
+    let backdrop = gtk::Picture::builder()
+        .can_shrink(true)
+        .content_fit(gtk::ContentFit::Cover)
+        .css_classes(vec!["art-backdrop"])
+        .visible(config.art_backdrop)
+        .build();
+
     let placeholder_label = gtk::Label::builder()
-        .label("?")
         .halign(gtk::Align::Center)
         .valign(gtk::Align::Center)
         .vexpand(true)
         .hexpand(true)
+        .visible(false)
+        .build();
+    let placeholder_icon = gtk::Image::builder()
+        .icon_name(FALLBACK_ART_ICON)
+        .pixel_size(art_size / 3)
+        .halign(gtk::Align::Center)
+        .valign(gtk::Align::Center)
+        .vexpand(true)
+        .hexpand(true)
+        .build();
+    // Stands in for the art at the same size, so the layout doesn't shift
+    let placeholder = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .width_request(art_size)
+        .height_request(art_size)
         .css_classes(vec!["album-art", "album-art-placeholder"])
         .build();
+    placeholder.append(&placeholder_label);
+    placeholder.append(&placeholder_icon);
 
     art_container.append(&album_art);
-    art_container.append(&placeholder_label);
+    art_container.append(&placeholder);
+
+    if config.desaturate_art_when_paused {
+        art_container.add_css_class("desaturate-when-paused");
+    }
 
     // Info section using proper Libadwaita patterns
     let info_box = gtk::Box::builder()
@@ -451,6 +1040,7 @@ fn build_content() -> MediaContent {
         .wrap_mode(gtk::pango::WrapMode::WordChar)
         .justify(gtk::Justification::Center)
         .ellipsize(gtk::pango::EllipsizeMode::End)
+        .max_width_chars(LABEL_MAX_WIDTH_CHARS)
         .lines(2)
         .halign(gtk::Align::Center)
         .build();
@@ -461,25 +1051,92 @@ fn build_content() -> MediaContent {
         .wrap(true)
         .justify(gtk::Justification::Center)
         .ellipsize(gtk::pango::EllipsizeMode::End)
+        .max_width_chars(LABEL_MAX_WIDTH_CHARS)
         .lines(1)
         .opacity(0.7)
         .halign(gtk::Align::Center)
         .build();
 
+    // Full-strength text in accessible mode instead of the dimmed hierarchy
+    if config.accessible_mode {
+        artist_label.set_opacity(1.0);
+    }
+
     let album_label = gtk::Label::builder()
         .label("")
         .css_classes(vec!["caption"])
         .wrap(true)
         .justify(gtk::Justification::Center)
         .ellipsize(gtk::pango::EllipsizeMode::End)
+        .max_width_chars(LABEL_MAX_WIDTH_CHARS)
         .lines(1)
         .opacity(0.55)
         .halign(gtk::Align::Center)
         .build();
 
-    info_box.append(&title_label);
-    info_box.append(&artist_label);
-    info_box.append(&album_label);
+    if config.accessible_mode {
+        album_label.set_opacity(1.0);
+    }
+
+    let identity_label = gtk::Label::builder()
+        .label("")
+        .css_classes(vec!["caption", "dim-label"])
+        .ellipsize(gtk::pango::EllipsizeMode::End)
+        .max_width_chars(LABEL_MAX_WIDTH_CHARS)
+        .halign(gtk::Align::Center)
+        .visible(false)
+        .build();
+
+    if config.compact_metadata {
+        // Title, artist and album share a single ellipsized line; the
+        // separate artist/album labels are left out of the layout.
+        title_label.set_wrap(false);
+        title_label.set_lines(1);
+        title_label.set_max_width_chars(COMPACT_LABEL_MAX_WIDTH_CHARS);
+        title_label.set_css_classes(&["title-3"]);
+        info_box.append(&title_label);
+    } else {
+        info_box.append(&title_label);
+        info_box.append(&artist_label);
+        info_box.append(&album_label);
+    }
+    info_box.append(&identity_label);
+
+    // Clickable only for players `MprisClient::set_rating` can rate, since
+    // MPRIS itself has no way to send a rating back
+    let rating_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(2)
+        .halign(gtk::Align::Center)
+        .visible(false)
+        .build();
+    for _ in 0..RATING_STARS {
+        rating_box.append(
+            &gtk::Image::builder()
+                .icon_name("non-starred-symbolic")
+                .pixel_size(12)
+                .opacity(0.7)
+                .build(),
+        );
+    }
+    info_box.append(&rating_box);
+
+    let gapless_label = gtk::Label::builder()
+        .label("Gapless")
+        .css_classes(vec!["caption", "dim-label"])
+        .tooltip_text("Gapless playback is on")
+        .halign(gtk::Align::Center)
+        .visible(false)
+        .build();
+    info_box.append(&gapless_label);
+
+    let stop_after_label = gtk::Label::builder()
+        .label("Stopping after this track")
+        .css_classes(vec!["caption", "dim-label"])
+        .halign(gtk::Align::Center)
+        .visible(false)
+        .build();
+    info_box.append(&stop_after_label);
 
     // Controls section with improved spacing and sizing
     let controls_box = gtk::Box::builder()
@@ -497,9 +1154,17 @@ fn build_content() -> MediaContent {
         .build();
 
     let play_pause_button = ProgressRingButton::new();
-    play_pause_button
-        .button()
-        .set_tooltip_text(Some("Play/Pause"));
+    play_pause_button.set_tooltip_text(Some("Play/Pause"));
+    play_pause_button.set_dim_when_paused(config.dim_ring_when_paused);
+    play_pause_button.set_countdown_mode(config.countdown_ring);
+    play_pause_button.set_hide_empty_track(config.hide_empty_ring_track);
+    play_pause_button.set_ring_width(config.ring_width);
+    if let Some(color) = config.ring_color.as_deref() {
+        match gdk::RGBA::parse(color) {
+            Ok(rgba) => play_pause_button.set_ring_color(Some(&rgba)),
+            Err(_) => eprintln!("Ignoring invalid ring_color: {}", color),
+        }
+    }
 
     let next_button = gtk::Button::builder()
         .icon_name("media-skip-forward-symbolic")
@@ -507,13 +1172,166 @@ fn build_content() -> MediaContent {
         .tooltip_text("Next")
         .build();
 
-    controls_box.append(&prev_button);
-    controls_box.append(&play_pause_button);
-    controls_box.append(&next_button);
+    let stop_button = gtk::Button::builder()
+        .icon_name("media-playback-stop-symbolic")
+        .css_classes(vec!["circular", "flat"])
+        .tooltip_text("Stop")
+        .build();
+
+    // Always goes to the start of the track, unlike previous, which may
+    // change tracks
+    let restart_button = gtk::Button::builder()
+        .icon_name("media-seek-backward-symbolic")
+        .css_classes(vec!["circular", "flat"])
+        .tooltip_text("Restart Track")
+        .sensitive(false)
+        .build();
+
+    // Shuffle and repeat stay hidden until the player reports support
+    let shuffle_button = gtk::ToggleButton::builder()
+        .icon_name("media-playlist-shuffle-symbolic")
+        .css_classes(vec!["circular", "flat"])
+        .tooltip_text("Shuffle")
+        .visible(false)
+        .build();
+
+    let repeat_button = gtk::Button::builder()
+        .icon_name("media-playlist-repeat-symbolic")
+        .css_classes(vec!["circular", "flat"])
+        .tooltip_text("Repeat")
+        .visible(false)
+        .build();
+
+    // Playback speed, for podcasts and audiobooks
+    let rate_list = gtk::StringList::new(&[]);
+    show_rate_choices(&rate_list, PLAYBACK_RATES);
+    let rate_dropdown = gtk::DropDown::builder()
+        .model(&rate_list)
+        .selected(nearest_rate_index(PLAYBACK_RATES, 1.0))
+        .tooltip_text("Playback Speed")
+        .valign(gtk::Align::Center)
+        .visible(false)
+        .build();
+
+    let controls = config.controls();
+    for control in &controls {
+        match control {
+            Control::Previous => controls_box.append(&prev_button),
+            Control::PlayPause => controls_box.append(&play_pause_button),
+            Control::Next => controls_box.append(&next_button),
+            Control::Stop => controls_box.append(&stop_button),
+            Control::Restart => controls_box.append(&restart_button),
+            Control::Shuffle => controls_box.append(&shuffle_button),
+            Control::Repeat => controls_box.append(&repeat_button),
+            Control::Rate => controls_box.append(&rate_dropdown),
+            // Lives in its own row below the art
+            Control::Volume => {}
+        }
+    }
 
-    container.append(&art_container);
+    let art_area: gtk::Widget = if config.fixed_art_area {
+        // Clamp both axes so a large cover can't grow the area past the
+        // placeholder's size and resize the window on track changes.
+        art_container.set_size_request(art_size, art_size);
+        let art_clamp_v = adw::Clamp::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .maximum_size(art_size)
+            .tightening_threshold(art_size)
+            .child(&art_container)
+            .build();
+        let art_clamp_h = adw::Clamp::builder()
+            .maximum_size(art_size)
+            .tightening_threshold(art_size)
+            .child(&art_clamp_v)
+            .build();
+        art_clamp_h.upcast()
+    } else {
+        art_container.clone().upcast()
+    };
+
+    if config.overlay_controls {
+        // Controls sit on a scrim along the bottom edge of the art. The
+        // overlay is centered so it, and the bar, take the art's width.
+        controls_box.set_margin_top(0);
+        controls_box.set_margin_bottom(0);
+        let art_overlay = gtk::Overlay::builder()
+            .child(&art_area)
+            .halign(gtk::Align::Center)
+            .valign(gtk::Align::Center)
+            .build();
+        art_overlay.add_overlay(&controls_box);
+        if config.controls_follow_cursor {
+            controls_box.set_valign(gtk::Align::Start);
+            controls_box.set_halign(gtk::Align::Start);
+            controls_box.add_css_class("art-controls-floating");
+            controls_box.set_visible(false);
+            setup_floating_controls(&art_overlay, &controls_box);
+        } else {
+            controls_box.set_valign(gtk::Align::End);
+            controls_box.set_halign(gtk::Align::Fill);
+            controls_box.add_css_class("art-controls");
+        }
+        container.append(&art_overlay);
+    } else {
+        container.append(&art_area);
+    }
     container.append(&info_box);
 
+    // Elapsed / total time. Clicking it swaps in an entry for typing an
+    // exact position.
+    let position_button = gtk::Button::builder()
+        .label("")
+        .css_classes(vec!["flat", "caption", "numeric"])
+        .tooltip_text("Go to position")
+        .halign(gtk::Align::Center)
+        .build();
+    let position_entry = gtk::Entry::builder()
+        .placeholder_text("M:SS")
+        .input_purpose(gtk::InputPurpose::Digits)
+        .width_chars(8)
+        .max_width_chars(8)
+        .xalign(0.5)
+        .halign(gtk::Align::Center)
+        .build();
+    let position_stack = gtk::Stack::builder()
+        .transition_type(gtk::StackTransitionType::Crossfade)
+        .hhomogeneous(false)
+        .halign(gtk::Align::Center)
+        .visible(false)
+        .build();
+    position_stack.add_named(&position_button, Some("label"));
+    position_stack.add_named(&position_entry, Some("entry"));
+    container.append(&position_stack);
+
+    let seek_scale = gtk::Scale::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .adjustment(&gtk::Adjustment::new(0.0, 0.0, 1.0, 1.0, 10.0, 0.0))
+        .draw_value(false)
+        .hexpand(true)
+        .tooltip_text("Seek")
+        .build();
+    let seek_elapsed_label = gtk::Label::builder()
+        .label("0:00")
+        .css_classes(vec!["caption", "numeric"])
+        .opacity(0.7)
+        .build();
+    let seek_length_label = gtk::Label::builder()
+        .label("-:--")
+        .css_classes(vec!["caption", "numeric"])
+        .opacity(0.7)
+        .build();
+    let seek_row = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(6)
+        .visible(false)
+        .build();
+    seek_row.append(&seek_elapsed_label);
+    seek_row.append(&seek_scale);
+    seek_row.append(&seek_length_label);
+    if config.show_seek_bar {
+        container.append(&seek_row);
+    }
+
     clamp.set_child(Some(&container));
 
     // Volume slider — native GNOME look, accent-colored, hidden when not controllable.
@@ -530,7 +1348,7 @@ fn build_content() -> MediaContent {
         .build();
 
     let volume_clamp = adw::Clamp::builder()
-        .maximum_size(280)
+        .maximum_size(content_width)
         .tightening_threshold(200)
         .margin_top(6)
         .margin_bottom(6)
@@ -540,9 +1358,59 @@ fn build_content() -> MediaContent {
         .margin_start(12)
         .margin_end(12)
         .build();
+    let mute_button = gtk::Button::builder()
+        .icon_name(volume_icon_name(0.0))
+        .tooltip_text("Mute")
+        .valign(gtk::Align::Center)
+        .css_classes(vec!["circular", "flat"])
+        .build();
+    volume_box.append(&mute_button);
     volume_box.append(&volume_scale);
     volume_clamp.set_child(Some(&volume_box));
 
+    // Upcoming tracks, for players with a TrackList. Double-click to jump.
+    let queue_list = gtk::StringList::new(&[]);
+    let queue_factory = gtk::SignalListItemFactory::new();
+    queue_factory.connect_setup(|_, item| {
+        let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+            return;
+        };
+        let label = gtk::Label::builder()
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .halign(gtk::Align::Start)
+            .margin_top(4)
+            .margin_bottom(4)
+            .build();
+        item.set_child(Some(&label));
+    });
+    queue_factory.connect_bind(|_, item| {
+        let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+            return;
+        };
+        let text = item.item().and_downcast::<gtk::StringObject>();
+        if let (Some(text), Some(label)) = (text, item.child().and_downcast::<gtk::Label>()) {
+            label.set_text(&text.string());
+        }
+    });
+    let queue_view = gtk::ListView::builder()
+        .model(&gtk::NoSelection::new(Some(queue_list.clone())))
+        .factory(&queue_factory)
+        .css_classes(vec!["navigation-sidebar"])
+        .build();
+    let queue_expander = gtk::Expander::builder()
+        .child(
+            &gtk::ScrolledWindow::builder()
+                .child(&queue_view)
+                .hscrollbar_policy(gtk::PolicyType::Never)
+                .propagate_natural_height(true)
+                .max_content_height(200)
+                .build(),
+        )
+        .margin_start(12)
+        .margin_end(12)
+        .visible(false)
+        .build();
+
     // Outer column: clamped content (expands) + volume + controls (anchored to bottom)
     let content_column = gtk::Box::builder()
         .orientation(gtk::Orientation::Vertical)
@@ -551,143 +1419,324 @@ fn build_content() -> MediaContent {
         .build();
     content_column.append(&clamp);
     content_column.append(&volume_clamp);
-    content_column.append(&controls_box);
+    content_column.append(&queue_expander);
+    if !config.overlay_controls {
+        content_column.append(&controls_box);
+    }
 
     // Hidden until a controllable player is detected.
     volume_clamp.set_visible(false);
 
-    art_container.set_visible(false);
-    placeholder_label.set_visible(false);
+    if config.fixed_art_area || config.overlay_controls {
+        // Reserve the art area up front with the placeholder showing; the
+        // overlaid controls need something to sit on.
+        album_art.set_visible(false);
+    } else {
+        art_container.set_visible(false);
+        placeholder.set_visible(false);
+    }
 
     MediaContent {
         container,
         content_column,
         clamp,
         album_art,
+        placeholder,
+        backdrop,
         placeholder_label,
+        placeholder_icon,
         art_container,
         title_label,
         artist_label,
         album_label,
+        rating_box,
+        can_rate: Arc::new(AtomicBool::new(false)),
+        identity_label,
+        gapless_label,
+        stop_after_label,
+        position_stack,
+        position_button,
+        seek_row,
+        seek_scale,
+        seek_elapsed_label,
+        seek_length_label,
+        seek_dragging: Arc::new(AtomicBool::new(false)),
+        position_entry,
+        identity_caption: config.player_identity_caption,
+        player_count: Arc::new(AtomicUsize::new(0)),
         play_pause_button,
+        compact_metadata: config.compact_metadata,
         prev_button,
         next_button,
+        stop_button,
+        restart_button,
+        shuffle_button,
+        repeat_button,
+        rate_dropdown,
+        rate_list,
+        rates: Arc::new(Mutex::new(PLAYBACK_RATES.to_vec())),
+        controls_updating: Arc::new(AtomicBool::new(false)),
+        loop_mode: Arc::new(Mutex::new(None)),
+        show_volume: controls.contains(&Control::Volume),
         volume_scale,
+        mute_button,
         volume_clamp,
+        queue_expander,
+        queue_list,
+        queue_view,
+        queue: Arc::default(),
+        pending_art_url: Arc::new(Mutex::new(None)),
+        notified_without_art: Arc::new(Mutex::new(None)),
     }
 }
 
-fn update_ui_widgets(
-    title_label: &gtk::Label,
-    artist_label: &gtk::Label,
-    album_label: &gtk::Label,
-    album_art: &gtk::Picture,
-    placeholder_label: &gtk::Label,
-    art_container: &gtk::Box,
-    play_pause_button: &ProgressRingButton,
-    info: &MediaInfo,
-    force_art_update: bool,
-) {
-    title_label.set_text(&info.title);
+fn update_ui_widgets(content: &MediaContent, info: &MediaInfo, force_art_update: bool) {
+    let title_label = &content.title_label;
+    let artist_label = &content.artist_label;
+    let album_label = &content.album_label;
+    let album_art = &content.album_art;
+    let placeholder_label = &content.placeholder_label;
+    let art_container = &content.art_container;
+    let play_pause_button = &content.play_pause_button;
+
+    if info.player_identity.is_empty() && info.title.is_empty() {
+        // The player went away
+        title_label.set_text("No media playing");
+    } else if content.compact_metadata {
+        title_label.set_text(&compact_metadata_line(info));
+    } else {
+        title_label.set_text(&info.title);
+    }
     artist_label.set_text(&info.artist);
-    album_label.set_text(&info.album);
+    let album_caption = album_caption(info);
+    album_label.set_text(&album_caption);
+    title_label.set_tooltip_text(info.comment.as_deref());
 
-    artist_label.set_visible(!info.artist.is_empty());
-    album_label.set_visible(!info.album.is_empty());
+    // Whitespace-only fields would otherwise leave a blank line and an
+    // extra gap in the info box
+    artist_label.set_visible(!info.artist.trim().is_empty());
+    album_label.set_visible(!album_caption.trim().is_empty());
 
-    // Update placeholder text: first letter of artist, or title, or "?"
-    let initial = if !info.artist.is_empty() {
-        info.artist.chars().next().unwrap_or('?').to_uppercase().to_string()
-    } else if !info.title.is_empty() {
-        info.title.chars().next().unwrap_or('?').to_uppercase().to_string()
-    } else {
-        "?".to_string()
+    content.rating_box.set_visible(info.rating.is_some());
+    if let Some(rating) = info.rating {
+        let stars = rating_stars(rating);
+        let mut star = content.rating_box.first_child();
+        for i in 0..RATING_STARS {
+            let Some(image) = star.and_downcast::<gtk::Image>() else {
+                break;
+            };
+            image.set_icon_name(Some(if i < stars {
+                "starred-symbolic"
+            } else {
+                "non-starred-symbolic"
+            }));
+            star = image.next_sibling();
+        }
+        let tooltip = format!("Rated {} of {}", stars, RATING_STARS);
+        content.rating_box.set_tooltip_text(Some(&if info.can_rate {
+            format!("{}, click a star to change it", tooltip)
+        } else {
+            tooltip
+        }));
+    }
+    content.can_rate.store(info.can_rate, Ordering::SeqCst);
+    content
+        .rating_box
+        .set_cursor_from_name(info.can_rate.then_some("pointer"));
+
+    let show_identity = !info.player_identity.is_empty()
+        && match content.identity_caption {
+            PlayerIdentityCaption::Never => false,
+            PlayerIdentityCaption::Always => true,
+            PlayerIdentityCaption::MultiplePlayers => {
+                content.player_count.load(Ordering::SeqCst) > 1
+            }
+        };
+    content
+        .identity_label
+        .set_text(&format!("via {}", info.player_identity));
+    content.identity_label.set_visible(show_identity);
+
+    let gapless = info
+        .extra_properties
+        .iter()
+        .any(|(label, value)| *label == GAPLESS_LABEL && value == "On");
+    content.gapless_label.set_visible(gapless);
+    content
+        .stop_after_label
+        .set_visible(info.stop_after_current);
+
+    content.restart_button.set_sensitive(info.can_seek);
+    // Radio streams often can't skip, so don't offer clicks that do nothing
+    content.prev_button.set_sensitive(info.can_go_previous);
+    content.next_button.set_sensitive(info.can_go_next);
+    content
+        .play_pause_button
+        .set_sensitive(info.can_play || info.can_pause);
+
+    content.controls_updating.store(true, Ordering::SeqCst);
+    content.shuffle_button.set_visible(info.shuffle.is_some());
+    content
+        .shuffle_button
+        .set_active(info.shuffle.unwrap_or(false));
+    content.rate_dropdown.set_visible(info.rate.is_some());
+    // Players with a fixed rate still show it, just greyed out
+    content
+        .rate_dropdown
+        .set_sensitive(info.rate_range.is_some());
+    if let Ok(mut rates) = content.rates.lock() {
+        let offered = playback_rates(info.rate_range);
+        if *rates != offered {
+            show_rate_choices(&content.rate_list, &offered);
+            *rates = offered;
+        }
+        if let Some(rate) = info.rate {
+            content
+                .rate_dropdown
+                .set_selected(nearest_rate_index(&rates, rate));
+        }
+    }
+    content.controls_updating.store(false, Ordering::SeqCst);
+
+    // Greyed out rather than hidden without loop support, so the controls
+    // don't shift around between players
+    content.repeat_button.set_visible(info.can_control);
+    content
+        .repeat_button
+        .set_sensitive(info.loop_mode.is_some());
+    if let Ok(mut loop_mode) = content.loop_mode.lock() {
+        *loop_mode = info.loop_mode;
+    }
+    let (repeat_icon, repeat_tooltip) = match info.loop_mode {
+        Some(LoopMode::Track) => ("media-playlist-repeat-song-symbolic", "Repeat Track"),
+        Some(LoopMode::Playlist) => ("media-playlist-repeat-symbolic", "Repeat All"),
+        Some(LoopMode::None) => ("media-playlist-repeat-symbolic", "Repeat Off"),
+        None => (
+            "media-playlist-repeat-symbolic",
+            "This player doesn't support repeat",
+        ),
     };
-    placeholder_label.set_text(&initial);
+    content.repeat_button.set_icon_name(repeat_icon);
+    content.repeat_button.set_tooltip_text(Some(repeat_tooltip));
+    // Same look as an inactive toggle when repeat is off
+    let repeat_off = info.loop_mode == Some(LoopMode::None);
+    content
+        .repeat_button
+        .set_opacity(if repeat_off { 0.5 } else { 1.0 });
+
+    // Placeholder for missing art: first letter of the artist, or title,
+    // or with neither, the player's icon
+    let initial = [&info.artist, &info.title]
+        .into_iter()
+        .find_map(|field| field.chars().next())
+        .map(|initial| initial.to_uppercase().to_string());
+    match initial {
+        Some(ref initial) => placeholder_label.set_text(initial),
+        None => {
+            let icon_name = info
+                .desktop_entry
+                .as_deref()
+                .and_then(player_icon_name)
+                .unwrap_or_else(|| FALLBACK_ART_ICON.to_string());
+            content.placeholder_icon.set_icon_name(Some(&icon_name));
+        }
+    }
+    placeholder_label.set_visible(initial.is_some());
+    content.placeholder_icon.set_visible(initial.is_none());
 
     // Handle album art loading with better error handling - only update when forced
     if force_art_update {
+        // Remember which art we're after so a slower, older download can't
+        // overwrite it when it finally completes
+        if let Ok(mut pending) = content.pending_art_url.lock() {
+            *pending = info.art_url.clone();
+        }
+        // Only a download still in flight for this art keeps it spinning
+        play_pause_button.set_loading(false);
+
         let has_art = info.art_url.as_ref().map_or(false, |u| !u.is_empty());
 
         if !has_art {
             // No art URL — show placeholder
-            album_art.set_paintable(gtk::gdk::Paintable::NONE);
-            album_art.set_visible(false);
-            placeholder_label.set_visible(true);
-            art_container.set_visible(true);
+            set_placeholder(content);
         } else if let Some(ref art_url) = info.art_url {
-            // Better URL handling: strip "file://" and handle URL encoding
-            let file_path = if let Some(stripped) = art_url.strip_prefix("file://") {
-                stripped
-            } else {
-                art_url
-            };
-
             // Handle different types of art URLs
-            if art_url.starts_with("http://") || art_url.starts_with("https://") {
-                // Clear art before attempting to load new art
-                album_art.set_paintable(gtk::gdk::Paintable::NONE);
-
-                // For web URLs, download the image data first
-                match reqwest::blocking::get(art_url.as_str()) {
-                    Ok(response) => {
-                        match response.bytes() {
-                            Ok(bytes) => {
-                                let bytes_vec = bytes.to_vec();
-                                // Create a memory input stream from the bytes
-                                let stream = gio::MemoryInputStream::from_bytes(
-                                    &glib::Bytes::from(&bytes_vec),
-                                );
-                                // Use GdkPixbuf's from_stream method which can handle various image formats
-                                match gdk_pixbuf::Pixbuf::from_stream(
-                                    &stream,
-                                    gio::Cancellable::NONE,
-                                ) {
-                                    Ok(pixbuf) => {
-                                        let texture = gdk::Texture::for_pixbuf(&pixbuf);
-                                        album_art.set_paintable(Some(&texture));
-                                        album_art.set_visible(true);
-                                        placeholder_label.set_visible(false);
-                                        art_container.set_visible(true);
-                                        album_art.queue_draw();
-                                        // Only log on initial load, not on retry mechanism
-                                        // Retry mechanism will handle logging
-                                    }
-                                    Err(e) => {
-                                        eprintln!(
-                                            "Failed to create pixbuf from web data {}: {}",
-                                            art_url, e
-                                        );
-                                        album_art.set_paintable(gtk::gdk::Paintable::NONE);
-                                        album_art.set_visible(false);
-                                        placeholder_label.set_visible(true);
-                                        art_container.set_visible(true);
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                eprintln!("Failed to read bytes from web {}: {}", art_url, e);
-                                album_art.set_paintable(gtk::gdk::Paintable::NONE);
-                                album_art.set_visible(false);
-                                placeholder_label.set_visible(true);
-                                art_container.set_visible(true);
-                            }
-                        }
+            if let Some((_, data)) = art_url
+                .strip_prefix("data:")
+                .and_then(|uri| uri.split_once(";base64,"))
+            {
+                // Embedded image data from the metadata
+                let bytes = glib::base64_decode(data);
+                let stream = gio::MemoryInputStream::from_bytes(&glib::Bytes::from_owned(bytes));
+                match gdk_pixbuf::Pixbuf::from_stream(&stream, gio::Cancellable::NONE) {
+                    Ok(pixbuf) => {
+                        let texture = gdk::Texture::for_pixbuf(&pixbuf);
+                        set_art(content, &texture);
                     }
                     Err(e) => {
-                        eprintln!("Failed to download image from web {}: {}", art_url, e);
-                        album_art.set_paintable(gtk::gdk::Paintable::NONE);
-                        album_art.set_visible(false);
-                        placeholder_label.set_visible(true);
-                        art_container.set_visible(true);
+                        eprintln!("Failed to decode embedded art: {}", e);
+                        set_placeholder(content);
                     }
                 }
+            } else if is_web_url(art_url) {
+                // The previous art stays up until this arrives. Fetched and
+                // decoded off the main thread, from the disk cache if it's
+                // been seen before. When skipping quickly several downloads
+                // can be in flight; ones for art that's no longer wanted are
+                // dropped before they start, or ignored when they finish.
+                let url = art_url.clone();
+                let content = content.clone();
+                // Cached art is quick enough not to need it
+                if art_cache::cached_path(&url).is_none() {
+                    play_pause_button.set_loading(true);
+                }
+                glib::spawn_future_local(async move {
+                    let result = gio::spawn_blocking({
+                        let url = url.clone();
+                        let pending = content.pending_art_url.clone();
+                        move || {
+                            let wanted = |pending: &Mutex<Option<String>>| {
+                                pending
+                                    .lock()
+                                    .is_ok_and(|p| p.as_deref() == Some(url.as_str()))
+                            };
+                            if !wanted(&pending) {
+                                return None;
+                            }
+                            Some(fetch_web_art(&url))
+                        }
+                    })
+                    .await;
+
+                    let still_current = content
+                        .pending_art_url
+                        .lock()
+                        .is_ok_and(|pending| pending.as_deref() == Some(url.as_str()));
+                    if !still_current {
+                        return;
+                    }
+                    content.play_pause_button.set_loading(false);
+
+                    match result {
+                        Ok(Some(Ok(texture))) => {
+                            set_art(&content, &texture);
+                            renotify_with_art(&content, &url);
+                        }
+                        Ok(Some(Err(e))) => {
+                            eprintln!("Failed to load art from {}: {}", url, e);
+                            set_placeholder(&content);
+                        }
+                        Ok(None) => {}
+                        Err(_) => {
+                            eprintln!("Art download for {} panicked", url);
+                        }
+                    }
+                });
             } else {
-                // For file:// or local paths, decode and load from filesystem
-                // Handle URL encoding for special characters
-                let decoded_path =
-                    urlencoding::decode(file_path).unwrap_or_else(|_| file_path.into());
-                let decoded_path_str = decoded_path.as_ref();
+                // For file:// or local paths, decode and load from filesystem,
+                // ignoring any query string or fragment on the URL
+                let decoded_path = art_local_path(art_url).unwrap_or_else(|| art_url.clone());
+                let decoded_path_str = decoded_path.as_str();
 
                 // Clear art before attempting to load new art
                 album_art.set_paintable(gtk::gdk::Paintable::NONE);
@@ -697,36 +1746,23 @@ fn update_ui_widgets(
                     true => match gdk_pixbuf::Pixbuf::from_file(decoded_path_str) {
                         Ok(pixbuf) => {
                             let texture = gdk::Texture::for_pixbuf(&pixbuf);
-                            album_art.set_paintable(Some(&texture));
-                            album_art.set_visible(true);
-                            placeholder_label.set_visible(false);
-                            art_container.set_visible(true);
-                            album_art.queue_draw();
+                            set_art(content, &texture);
                             eprintln!("Successfully loaded art from file: {}", decoded_path);
                         }
                         Err(e) => {
                             eprintln!("Failed to load pixbuf from {}: {}", decoded_path, e);
-                            album_art.set_paintable(gtk::gdk::Paintable::NONE);
-                            album_art.set_visible(false);
-                            placeholder_label.set_visible(true);
-                            art_container.set_visible(true);
+                            set_placeholder(content);
                         }
                     },
                     false => {
                         eprintln!("Art file does not exist: {}", decoded_path);
-                        album_art.set_paintable(gtk::gdk::Paintable::NONE);
-                        album_art.set_visible(false);
-                        placeholder_label.set_visible(true);
-                        art_container.set_visible(true);
+                        set_placeholder(content);
                     }
                 }
             }
         } else {
             // No art URL provided, show placeholder
-            album_art.set_paintable(gtk::gdk::Paintable::NONE);
-            album_art.set_visible(false);
-            placeholder_label.set_visible(true);
-            art_container.set_visible(true);
+            set_placeholder(content);
         }
     }
 
@@ -742,8 +1778,233 @@ fn update_ui_widgets(
     play_pause_button.set_icon_name(icon_name);
     play_pause_button.set_paused_style(is_paused);
 
-    // Update progress ring
-    if let (Some(position), Some(length)) = (info.position, info.length) {
+    // Only a real pause (not stopped) gets the paused art treatment
+    if info.status == PlayerStatus::Paused {
+        art_container.add_css_class("paused");
+    } else {
+        art_container.remove_css_class("paused");
+    }
+
+    update_queue(content, info.has_track_list, info.queue.as_deref());
+    update_progress(content, info);
+}
+
+/// Download (or read from the cache) and decode the art at `url`. Blocks;
+/// run it off the main thread.
+fn fetch_web_art(url: &str) -> anyhow::Result<gdk::Texture> {
+    let bytes = match art_cache::load(url) {
+        Some(bytes) => glib::Bytes::from_owned(bytes),
+        None => {
+            let bytes = reqwest::blocking::Client::builder()
+                .timeout(ART_DOWNLOAD_TIMEOUT)
+                .build()?
+                .get(url)
+                .send()?
+                .error_for_status()?
+                .bytes()?;
+            if let Err(e) = art_cache::store(url, &bytes) {
+                eprintln!("Failed to cache art from {}: {}", url, e);
+            }
+            glib::Bytes::from_owned(bytes)
+        }
+    };
+    let stream = gio::MemoryInputStream::from_bytes(&bytes);
+    let pixbuf = gdk_pixbuf::Pixbuf::from_stream(&stream, gio::Cancellable::NONE)?;
+    Ok(gdk::Texture::for_pixbuf(&pixbuf))
+}
+
+/// Show the player's upcoming tracks, or hide the queue if it has no track
+/// list. The queue is only read while the expander is open, so until then
+/// it's `None` and the expander is shown without a count. The list is only
+/// rebuilt when the queue changes, so it keeps its scroll position between
+/// updates.
+fn update_queue(content: &MediaContent, has_track_list: bool, queue: Option<&[QueuedTrack]>) {
+    let tracks = match queue {
+        Some(tracks) => tracks,
+        None if has_track_list => {
+            content.queue_expander.set_visible(true);
+            content.queue_expander.set_label(Some("Up Next"));
+            return;
+        }
+        None => &[],
+    };
+    content.queue_expander.set_visible(!tracks.is_empty());
+    content
+        .queue_expander
+        .set_label(Some(&format!("Up Next ({})", tracks.len())));
+
+    let Ok(mut shown) = content.queue.lock() else {
+        return;
+    };
+    if shown.as_slice() == tracks {
+        return;
+    }
+    let lines: Vec<String> = tracks.iter().map(queue_line).collect();
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    content
+        .queue_list
+        .splice(0, content.queue_list.n_items(), &lines);
+    *shown = tracks.to_vec();
+}
+
+fn queue_line(track: &QueuedTrack) -> String {
+    match (track.title.is_empty(), track.artist.is_empty()) {
+        (true, _) => "Unknown track".to_string(),
+        (false, true) => track.title.clone(),
+        (false, false) => format!("{} — {}", track.title, track.artist),
+    }
+}
+
+/// Hide the art and show the placeholder in its place.
+fn set_placeholder(content: &MediaContent) {
+    content.album_art.set_paintable(gdk::Paintable::NONE);
+    content.backdrop.set_paintable(gdk::Paintable::NONE);
+    content.album_art.set_visible(false);
+    content.placeholder.set_visible(true);
+    content.art_container.set_visible(true);
+}
+
+fn set_art(content: &MediaContent, texture: &gdk::Texture) {
+    content.album_art.set_paintable(Some(texture));
+    content
+        .backdrop
+        .set_paintable(Some(&blurred_backdrop(texture)));
+    content.album_art.set_visible(true);
+    content.placeholder.set_visible(false);
+    content.art_container.set_visible(true);
+}
+
+/// A heavily blurred copy of `texture` for the backdrop. Blurring it once
+/// here is far cheaper than a CSS blur filter, which is redone on every
+/// frame the window draws.
+fn blurred_backdrop(texture: &gdk::Texture) -> gdk::Texture {
+    let mut downloader = gdk::TextureDownloader::new(texture);
+    downloader.set_format(gdk::MemoryFormat::R8g8b8a8Premultiplied);
+    let (pixels, stride) = downloader.download_bytes();
+    let (width, height) = (texture.width() as usize, texture.height() as usize);
+    let (pixels, width, height) = shrink_and_blur(&pixels, width, height, stride);
+    gdk::MemoryTexture::new(
+        width as i32,
+        height as i32,
+        gdk::MemoryFormat::R8g8b8a8Premultiplied,
+        &glib::Bytes::from_owned(pixels),
+        width * 4,
+    )
+    .upcast()
+}
+
+/// Average 4-byte pixels down to at most `BACKDROP_SIZE` on the longest
+/// side, then box blur what's left. Stretched back up to fill the window
+/// with linear filtering, that's as soft as a large blur radius.
+fn shrink_and_blur(
+    pixels: &[u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+) -> (Vec<u8>, usize, usize) {
+    let scale = width.max(height).div_ceil(BACKDROP_SIZE).max(1);
+    let (small_width, small_height) = (width.div_ceil(scale), height.div_ceil(scale));
+
+    let mut small = vec![0u8; small_width * small_height * 4];
+    for y in 0..small_height {
+        for x in 0..small_width {
+            let mut sum = [0u32; 4];
+            let mut count = 0;
+            for sy in y * scale..((y + 1) * scale).min(height) {
+                for sx in x * scale..((x + 1) * scale).min(width) {
+                    let at = sy * stride + sx * 4;
+                    for (total, value) in sum.iter_mut().zip(&pixels[at..at + 4]) {
+                        *total += *value as u32;
+                    }
+                    count += 1;
+                }
+            }
+            let at = (y * small_width + x) * 4;
+            for (out, total) in small[at..at + 4].iter_mut().zip(sum) {
+                *out = (total / count) as u8;
+            }
+        }
+    }
+
+    // Three box blur passes come close to a gaussian
+    for _ in 0..3 {
+        small = box_blur(&small, small_width, small_height);
+    }
+    (small, small_width, small_height)
+}
+
+/// One pass of a 3×3 box blur over tightly packed 4-byte pixels, clamping
+/// at the edges.
+fn box_blur(pixels: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut blurred = vec![0u8; pixels.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [0u32; 4];
+            let mut count = 0;
+            for ny in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+                for nx in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+                    let at = (ny * width + nx) * 4;
+                    for (total, value) in sum.iter_mut().zip(&pixels[at..at + 4]) {
+                        *total += *value as u32;
+                    }
+                    count += 1;
+                }
+            }
+            let at = (y * width + x) * 4;
+            for (out, total) in blurred[at..at + 4].iter_mut().zip(sum) {
+                *out = (total / count) as u8;
+            }
+        }
+    }
+    blurred
+}
+
+/// Update everything that follows the playback position. Called on its own
+/// when nothing else about the track has changed.
+fn update_progress(content: &MediaContent, info: &MediaInfo) {
+    let play_pause_button = &content.play_pause_button;
+    let dragging = content.seek_dragging.load(Ordering::SeqCst);
+    match (info.position, info.length) {
+        (Some(position), Some(length)) if !length.is_zero() => {
+            // While scrubbing the label shows the drag target instead
+            if !dragging {
+                content.position_button.set_label(&format!(
+                    "{} / {}",
+                    format_time(position),
+                    format_time(length)
+                ));
+                content.seek_scale.set_range(0.0, length.as_secs_f64());
+                content.seek_scale.set_value(position.as_secs_f64());
+                content.seek_elapsed_label.set_text(&format_time(position));
+            }
+            content.seek_length_label.set_text(&format_time(length));
+            content.position_button.set_sensitive(info.can_seek);
+            content.position_stack.set_visible(true);
+            content.seek_scale.set_sensitive(info.can_seek);
+            content.seek_row.set_visible(true);
+        }
+        // Streams without a length: the elapsed time still counts, but
+        // there's nothing to seek along
+        (Some(position), _) => {
+            content.position_stack.set_visible_child_name("label");
+            content.position_stack.set_visible(false);
+            content.seek_scale.set_value(0.0);
+            content.seek_scale.set_sensitive(false);
+            content.seek_elapsed_label.set_text(&format_time(position));
+            content.seek_length_label.set_text("-:--");
+            content.seek_row.set_visible(true);
+        }
+        _ => {
+            content.position_stack.set_visible_child_name("label");
+            content.position_stack.set_visible(false);
+            content.seek_row.set_visible(false);
+        }
+    }
+
+    // Update progress ring. Some players keep reporting the old position
+    // after a stop, but a stopped track starts over from the beginning.
+    let stopped = info.status == PlayerStatus::Stopped;
+    if let (false, Some(position), Some(length)) = (stopped, info.position, info.length) {
         let progress = if length.as_secs() > 0 {
             position.as_secs_f64() / length.as_secs_f64()
         } else {
@@ -753,102 +2014,1682 @@ fn update_ui_widgets(
     } else {
         play_pause_button.set_progress(0.0);
     }
+    play_pause_button.set_seekable(info.can_seek && info.length.is_some_and(|l| !l.is_zero()));
+    // Sweep smoothly between updates instead of jumping every poll
+    let rate = match info.length {
+        Some(length) if info.status == PlayerStatus::Playing && !length.is_zero() => {
+            info.rate.unwrap_or(1.0) / length.as_secs_f64()
+        }
+        _ => 0.0,
+    };
+    play_pause_button.set_progress_rate(rate);
+
+    match (info.buffered, info.length) {
+        (Some(buffered), Some(length)) if length.as_secs() > 0 => {
+            play_pause_button.set_buffered(buffered.as_secs_f64() / length.as_secs_f64());
+        }
+        _ => play_pause_button.set_buffered(0.0),
+    }
+}
+
+/// Clear everything that shows the current player's position.
+fn reset_progress(content: &MediaContent) {
+    content.play_pause_button.set_progress(0.0);
+    content.play_pause_button.set_progress_rate(0.0);
+    content.play_pause_button.set_buffered(0.0);
+    content.position_stack.set_visible_child_name("label");
+    content.position_stack.set_visible(false);
+    content.seek_scale.set_value(0.0);
+    content.seek_row.set_visible(false);
+}
+
+/// The entries in `PLAYBACK_RATES` within `range`, or all of them for
+/// players with a fixed rate, whose dropdown is only there to show it.
+fn playback_rates(range: Option<(f64, f64)>) -> Vec<f64> {
+    let Some((min, max)) = range else {
+        return PLAYBACK_RATES.to_vec();
+    };
+    PLAYBACK_RATES
+        .iter()
+        .copied()
+        .filter(|rate| (min..=max).contains(rate))
+        .collect()
+}
+
+fn show_rate_choices(list: &gtk::StringList, rates: &[f64]) {
+    let labels: Vec<String> = rates.iter().map(|r| format!("{}×", r)).collect();
+    let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    list.splice(0, list.n_items(), &labels);
+}
+
+/// The index of the entry in `rates` closest to `rate`.
+fn nearest_rate_index(rates: &[f64], rate: f64) -> u32 {
+    rates
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| (*a - rate).abs().total_cmp(&(*b - rate).abs()))
+        .map_or(0, |(i, _)| i as u32)
+}
+
+/// A 0.0–1.0 rating as a number of whole stars out of `RATING_STARS`.
+fn rating_stars(rating: f64) -> u32 {
+    (rating.clamp(0.0, 1.0) * RATING_STARS as f64).round() as u32
+}
+
+/// A longer duration to the minute, e.g. "1 h 05 min". Coarse enough that
+/// the details panel doesn't have to be rebuilt every second.
+fn format_minutes(time: Duration) -> String {
+    let minutes = time.as_secs() / 60;
+    match minutes {
+        0 => "Under a minute".to_string(),
+        1..=59 => format!("{} min", minutes),
+        _ => format!("{} h {:02} min", minutes / 60, minutes % 60),
+    }
+}
+
+/// What's left of the current track plus everything queued after it.
+fn playlist_remaining(info: &MediaInfo) -> Option<Duration> {
+    let current_left = info
+        .length
+        .zip(info.position)
+        .map_or(Duration::ZERO, |(length, position)| {
+            length.saturating_sub(position)
+        });
+    info.queued_after.map(|queued| queued + current_left)
+}
+
+/// Parse `SS`, `M:SS` or `H:MM:SS`.
+fn parse_time(text: &str) -> Option<Duration> {
+    let parts: Vec<&str> = text.trim().split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+
+    let mut secs: u64 = 0;
+    for (i, part) in parts.iter().enumerate() {
+        let value: u64 = part.parse().ok()?;
+        // Every field after the first is a two-digit 0-59 value
+        if i > 0 && (part.len() != 2 || value >= 60) {
+            return None;
+        }
+        secs = secs * 60 + value;
+    }
+    Some(Duration::from_secs(secs))
+}
+
+/// "1:23 Song — Artist", or just the app name when nothing is playing.
+fn window_title_with_position(info: &MediaInfo) -> String {
+    if info.title.is_empty() {
+        return "Empress".to_string();
+    }
+
+    let mut title = match info.position {
+        Some(position) => format!("{} {}", format_time(position), info.title),
+        None => info.title.clone(),
+    };
+    if !info.artist.trim().is_empty() {
+        title.push_str(" — ");
+        title.push_str(&info.artist);
+    }
+    title
+}
+
+/// Stands in for missing art when there's no track to take a letter from.
+const FALLBACK_ART_ICON: &str = "folder-music-symbolic";
+
+/// Shown for players whose desktop entry has no icon we can find.
+const FALLBACK_PLAYER_ICON: &str = "multimedia-player-symbolic";
+
+fn player_entry(player: &AvailablePlayer) -> PlayerEntry {
+    let icon_name = player
+        .desktop_entry
+        .as_deref()
+        .and_then(player_icon_name)
+        .unwrap_or_else(|| FALLBACK_PLAYER_ICON.to_string());
+    PlayerEntry::new(&player.identity, &icon_name)
+}
+
+/// The themed icon for a player's desktop entry: the one its `.desktop`
+/// file names if it's installed, otherwise an icon named after the entry
+/// itself, which is what most players use.
+fn player_icon_name(desktop_entry: &str) -> Option<String> {
+    let display = gdk::Display::default()?;
+    let theme = gtk::IconTheme::for_display(&display);
+
+    let from_app_info = gio::DesktopAppInfo::new(&format!("{}.desktop", desktop_entry))
+        .and_then(|app_info| app_info.icon())
+        .and_downcast::<gio::ThemedIcon>()
+        .map(|icon| icon.names())
+        .unwrap_or_default();
+    from_app_info
+        .iter()
+        .map(|name| name.to_string())
+        .chain([desktop_entry.to_string(), desktop_entry.to_lowercase()])
+        .find(|name| theme.has_icon(name))
+}
+
+/// Rows for the player menu: the player's icon beside its name.
+fn player_entry_factory() -> gtk::SignalListItemFactory {
+    let factory = gtk::SignalListItemFactory::new();
+    factory.connect_setup(|_, item| {
+        let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+            return;
+        };
+        let row = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+        row.append(&gtk::Image::new());
+        row.append(&gtk::Label::new(None));
+        item.set_child(Some(&row));
+    });
+    factory.connect_bind(|_, item| {
+        let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+            return;
+        };
+        let Some(entry) = item.item().and_downcast::<PlayerEntry>() else {
+            return;
+        };
+        let Some(row) = item.child() else {
+            return;
+        };
+        if let Some(image) = row.first_child().and_downcast::<gtk::Image>() {
+            image.set_icon_name(Some(&entry.icon_name()));
+        }
+        if let Some(label) = row.last_child().and_downcast::<gtk::Label>() {
+            label.set_text(&entry.identity());
+        }
+    });
+    factory
+}
+
+/// The album followed by the disc and track numbers the player gives,
+/// e.g. "Album • Disc 2 • Track 3".
+fn album_caption(info: &MediaInfo) -> String {
+    let disc = info.disc_number.map(|n| format!("Disc {}", n));
+    let track = info.track_number.map(|n| format!("Track {}", n));
+    Some(info.album.trim().to_string())
+        .filter(|album| !album.is_empty())
+        .into_iter()
+        .chain(disc)
+        .chain(track)
+        .collect::<Vec<_>>()
+        .join(" • ")
+}
+
+/// The speaker icon for `volume`, from muted to high.
+fn volume_icon_name(volume: f64) -> &'static str {
+    if volume <= 0.0 {
+        "audio-volume-muted-symbolic"
+    } else if volume < 0.34 {
+        "audio-volume-low-symbolic"
+    } else if volume < 0.67 {
+        "audio-volume-medium-symbolic"
+    } else {
+        "audio-volume-high-symbolic"
+    }
+}
+
+fn update_mute_button(content: &MediaContent, volume: f64) {
+    content.mute_button.set_icon_name(volume_icon_name(volume));
+    content
+        .mute_button
+        .set_tooltip_text(Some(if volume <= 0.0 { "Unmute" } else { "Mute" }));
+}
+
+/// "Title • Artist • Album", skipping whichever fields are empty.
+fn compact_metadata_line(info: &MediaInfo) -> String {
+    [&info.title, &info.artist, &info.album]
+        .into_iter()
+        .filter(|field| !field.is_empty())
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" • ")
+}
+
+fn setup_controls(
+    content: &MediaContent,
+    client: MprisClient,
+    volume_updating: Arc<AtomicBool>,
+    can_seek: Arc<AtomicBool>,
+    live_config: Arc<Mutex<Config>>,
+) {
+    content.play_pause_button.connect_clicked({
+        let client = client.clone();
+        move |_| {
+            let _ = client.play_pause();
+        }
+    });
+
+    content.next_button.connect_clicked({
+        let client = client.clone();
+        move |_| {
+            let _ = client.next();
+        }
+    });
+
+    content.prev_button.connect_clicked({
+        let client = client.clone();
+        move |_| {
+            let _ = client.previous();
+        }
+    });
+
+    content.stop_button.connect_clicked({
+        let client = client.clone();
+        move |_| {
+            let _ = client.stop();
+        }
+    });
+
+    content.restart_button.connect_clicked({
+        let client = client.clone();
+        move |_| {
+            let _ = client.set_position(Duration::ZERO);
+        }
+    });
+
+    // Only read the TrackList while someone is looking at it
+    content.queue_expander.connect_expanded_notify({
+        let client = client.clone();
+        let live_config = live_config.clone();
+        move |expander| {
+            let show_playlist_remaining = live_config
+                .lock()
+                .map(|config| config.show_playlist_remaining)
+                .unwrap_or_default();
+            client.set_fetch_queue(show_playlist_remaining || expander.is_expanded());
+        }
+    });
+
+    // Clicking a star rates the track that many stars; clicking the
+    // highest lit star again clears the rating
+    let rating_click = gtk::GestureClick::new();
+    rating_click.connect_released({
+        let client = client.clone();
+        let rating_box = content.rating_box.clone();
+        let can_rate = content.can_rate.clone();
+        move |_, _, x, y| {
+            if !can_rate.load(Ordering::SeqCst) {
+                return;
+            }
+            let Some(clicked) = rating_box.pick(x, y, gtk::PickFlags::DEFAULT) else {
+                return;
+            };
+            let mut stars = Vec::new();
+            let mut star = rating_box.first_child();
+            while let Some(image) = star.and_downcast::<gtk::Image>() {
+                star = image.next_sibling();
+                stars.push(image);
+            }
+            let Some(index) = stars.iter().position(|image| *image == clicked) else {
+                return;
+            };
+            let lit = stars
+                .iter()
+                .filter(|image| image.icon_name().as_deref() == Some("starred-symbolic"))
+                .count();
+            let new_stars = if lit == index + 1 { 0 } else { index + 1 };
+            let _ = client.set_rating(new_stars as f64 / RATING_STARS as f64);
+        }
+    });
+    content.rating_box.add_controller(rating_click);
+
+    content.queue_view.connect_activate({
+        let client = client.clone();
+        let queue = content.queue.clone();
+        move |_, position| {
+            let id = queue
+                .lock()
+                .ok()
+                .and_then(|queue| queue.get(position as usize).map(|track| track.id.clone()));
+            if let Some(id) = id {
+                let _ = client.go_to(&id);
+            }
+        }
+    });
+
+    content.shuffle_button.connect_toggled({
+        let client = client.clone();
+        let controls_updating = content.controls_updating.clone();
+        move |button| {
+            if controls_updating.load(Ordering::SeqCst) {
+                return;
+            }
+            let _ = client.set_shuffle(button.is_active());
+        }
+    });
+
+    content.rate_dropdown.connect_selected_notify({
+        let client = client.clone();
+        let rates = content.rates.clone();
+        let controls_updating = content.controls_updating.clone();
+        move |dropdown| {
+            if controls_updating.load(Ordering::SeqCst) {
+                return;
+            }
+            let rate = rates
+                .lock()
+                .ok()
+                .and_then(|rates| rates.get(dropdown.selected() as usize).copied());
+            if let Some(rate) = rate {
+                let _ = client.set_rate(rate);
+            }
+        }
+    });
+
+    content.repeat_button.connect_clicked({
+        let client = client.clone();
+        let loop_mode = content.loop_mode.clone();
+        move |_| {
+            let current = loop_mode.lock().ok().and_then(|mode| *mode);
+            if let Some(mode) = current {
+                let _ = client.set_loop(mode.next());
+            }
+        }
+    });
+
+    // Volume slider → MPRIS
+    content.volume_scale.connect_value_changed({
+        let client = client.clone();
+        move |scale| {
+            if volume_updating.load(Ordering::SeqCst) {
+                return;
+            }
+            let _ = client.set_volume(scale.value());
+        }
+    });
+
+    content.mute_button.connect_clicked({
+        let client = client.clone();
+        move |_| {
+            let _ = client.toggle_mute();
+        }
+    });
+
+    // Add scroll event handler for seeking. Deltas are accumulated so that
+    // high-resolution touchpads, which send many tiny deltas per gesture,
+    // seek proportionally instead of a whole step per event.
+    let scroll_controller = gtk::EventControllerScroll::new(
+        gtk::EventControllerScrollFlags::VERTICAL | gtk::EventControllerScrollFlags::KINETIC,
+    );
+    let scroll_accumulator = Arc::new(Mutex::new(0.0_f64));
+
+    scroll_controller.connect_scroll({
+        let client = client.clone();
+        let scroll_accumulator = scroll_accumulator.clone();
+        move |controller, _dx, dy| {
+            if !can_seek.load(Ordering::SeqCst) {
+                return glib::Propagation::Proceed;
+            }
+
+            // A wheel notch is one step; touchpads report surface pixels.
+            let threshold = match controller.unit() {
+                gtk::gdk::ScrollUnit::Wheel => 1.0,
+                _ => SCROLL_SURFACE_STEP,
+            };
+
+            let steps = if let Ok(mut accumulated) = scroll_accumulator.lock() {
+                *accumulated += dy;
+                let steps = (*accumulated / threshold).trunc();
+                *accumulated -= steps * threshold;
+                steps as i64
+            } else {
+                0
+            };
+
+            // Read each time, since the preferences can change them
+            let Ok(config) = live_config.lock() else {
+                return glib::Propagation::Stop;
+            };
+            if steps != 0 {
+                let shift = controller
+                    .current_event_state()
+                    .contains(gdk::ModifierType::SHIFT_MASK);
+                let seconds = if shift {
+                    config.scroll_seek_shift_seconds as i64
+                } else {
+                    config.scroll_seek_seconds as i64
+                };
+                // Scrolling down goes back, unless the user wants it the
+                // other way
+                let direction = if config.natural_scroll { 1 } else { -1 };
+                // dy > 0 means scrolling down. MPRIS seek uses microseconds
+                let offset_micros = direction * steps * seconds * 1_000_000;
+                let _ = client.seek(offset_micros);
+            }
+
+            glib::Propagation::Stop
+        }
+    });
+
+    // Drop any leftover partial step when the gesture ends, and ignore the
+    // kinetic fling so lifting a finger doesn't keep seeking.
+    scroll_controller.connect_scroll_end({
+        let scroll_accumulator = scroll_accumulator.clone();
+        move |_| {
+            if let Ok(mut accumulated) = scroll_accumulator.lock() {
+                *accumulated = 0.0;
+            }
+        }
+    });
+    scroll_controller.connect_decelerate(move |_, _vel_x, _vel_y| {
+        if let Ok(mut accumulated) = scroll_accumulator.lock() {
+            *accumulated = 0.0;
+        }
+    });
+
+    content.play_pause_button.add_controller(scroll_controller);
+}
+
+fn setup_position_editing(
+    content: &MediaContent,
+    client: MprisClient,
+    latest_info: Arc<Mutex<MediaInfo>>,
+) {
+    let stack = &content.position_stack;
+    let entry = &content.position_entry;
+
+    content.position_button.connect_clicked({
+        let stack = stack.clone();
+        let entry = entry.clone();
+        let latest_info = latest_info.clone();
+        move |_| {
+            let position = latest_info.lock().ok().and_then(|info| info.position);
+            entry.set_text(&position.map(format_time).unwrap_or_default());
+            stack.set_visible_child_name("entry");
+            entry.grab_focus();
+        }
+    });
+
+    // Enter jumps there if it's a valid time within the track; anything
+    // else just puts the label back.
+    entry.connect_activate({
+        let stack = stack.clone();
+        move |entry| {
+            let (length, track_id) = match latest_info.lock() {
+                Ok(info) => (info.length, info.track_id.clone()),
+                Err(_) => (None, None),
+            };
+            match (parse_time(&entry.text()), length) {
+                (Some(target), Some(length)) if target <= length => {
+                    let _ = match track_id {
+                        Some(track_id) => client.set_track_position(&track_id, target),
+                        None => client.set_position(target),
+                    };
+                }
+                _ => {}
+            }
+            stack.set_visible_child_name("label");
+        }
+    });
+
+    let key_controller = gtk::EventControllerKey::new();
+    key_controller.connect_key_pressed({
+        let stack = stack.clone();
+        move |_, key, _code, _modifier| {
+            if key == gtk::gdk::Key::Escape {
+                stack.set_visible_child_name("label");
+                return glib::Propagation::Stop;
+            }
+            glib::Propagation::Proceed
+        }
+    });
+    entry.add_controller(key_controller);
+
+    let focus_controller = gtk::EventControllerFocus::new();
+    focus_controller.connect_leave({
+        let stack = stack.clone();
+        move |_| {
+            stack.set_visible_child_name("label");
+        }
+    });
+    entry.add_controller(focus_controller);
+}
+
+/// Show the tray icon, and show or hide the window when it asks.
+#[cfg(feature = "tray")]
+fn setup_tray(
+    window: &adw::ApplicationWindow,
+    client: MprisClient,
+    latest_info: Arc<Mutex<MediaInfo>>,
+    raise_on: &[String],
+) {
+    let events = tray::spawn(client, latest_info, raise_on);
+    let window = window.downgrade();
+    glib::timeout_add_local(Duration::from_millis(250), move || {
+        let Some(window) = window.upgrade() else {
+            return glib::ControlFlow::Break;
+        };
+        for event in events.try_iter() {
+            match event {
+                TrayEvent::ToggleWindow if window.is_visible() => window.set_visible(false),
+                TrayEvent::ToggleWindow | TrayEvent::Present => window.present(),
+            }
+        }
+        glib::ControlFlow::Continue
+    });
+}
+
+/// Keep the all-players page filled with a card per running player. Players
+/// are only polled while the page is shown, and cards are updated in place
+/// so focus and hover survive a change.
+fn setup_all_players(view_stack: &adw::ViewStack, list: &gtk::ListBox, client: MprisClient) {
+    let is_shown =
+        |stack: &adw::ViewStack| stack.visible_child_name().as_deref() == Some(ALL_PLAYERS_PAGE);
+    let (shown_sender, shown) = std::sync::mpsc::channel();
+    let _ = shown_sender.send(is_shown(view_stack));
+    // Dropping the sender with the stack lets the monitor thread exit
+    view_stack.connect_visible_child_name_notify(move |stack| {
+        let _ = shown_sender.send(is_shown(stack));
+    });
+
+    let players = client.start_monitoring_all(shown);
+    let list = list.downgrade();
+    let mut cards: Vec<PlayerCard> = Vec::new();
+    glib::timeout_add_local(Duration::from_millis(250), move || {
+        let Some(list) = list.upgrade() else {
+            return glib::ControlFlow::Break;
+        };
+        if let Some(players) = players.try_iter().last() {
+            cards.retain(|card| {
+                let running = players
+                    .iter()
+                    .any(|(bus_name, _)| *bus_name == card.bus_name);
+                if !running {
+                    list.remove(&card.row);
+                }
+                running
+            });
+            for (bus_name, info) in &players {
+                match cards.iter_mut().find(|card| card.bus_name == *bus_name) {
+                    Some(card) => card.update(info),
+                    None => {
+                        let mut card = PlayerCard::new(bus_name, &client);
+                        card.update(info);
+                        list.append(&card.row);
+                        cards.push(card);
+                    }
+                }
+            }
+        }
+        glib::ControlFlow::Continue
+    });
+}
+
+/// One player on the all-players page: its art (or icon), what it's
+/// playing, and previous/play-pause/next buttons that act on it alone.
+struct PlayerCard {
+    bus_name: String,
+    row: gtk::ListBoxRow,
+    art: gtk::Image,
+    /// The art URL and desktop entry the image was last set from.
+    art_source: Option<(Option<String>, Option<String>)>,
+    title_label: gtk::Label,
+    artist_label: gtk::Label,
+    identity_label: gtk::Label,
+    previous_button: gtk::Button,
+    play_pause_button: gtk::Button,
+    next_button: gtk::Button,
+}
+
+impl PlayerCard {
+    fn new(bus_name: &str, client: &MprisClient) -> Self {
+        let card = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(12)
+            .margin_top(6)
+            .margin_bottom(6)
+            .margin_start(6)
+            .margin_end(6)
+            .build();
+
+        let art = gtk::Image::builder()
+            .pixel_size(PLAYER_CARD_ART_SIZE)
+            .build();
+        card.append(&art);
+
+        let labels = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .valign(gtk::Align::Center)
+            .hexpand(true)
+            .build();
+        let line = |class: &str| {
+            let label = gtk::Label::builder()
+                .xalign(0.0)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .max_width_chars(LABEL_MAX_WIDTH_CHARS)
+                .css_classes(vec![class])
+                .build();
+            labels.append(&label);
+            label
+        };
+        let title_label = line("heading");
+        let artist_label = line("caption");
+        let identity_label = line("dim-label");
+        card.append(&labels);
+
+        type PlayerCommand = fn(&MprisClient, &str) -> anyhow::Result<()>;
+        let button = |icon_name: &str, tooltip: &str, send: PlayerCommand| {
+            let button = gtk::Button::builder()
+                .icon_name(icon_name)
+                .tooltip_text(tooltip)
+                .valign(gtk::Align::Center)
+                .css_classes(vec!["circular", "flat"])
+                .build();
+            let client = client.clone();
+            let bus_name = bus_name.to_string();
+            button.connect_clicked(move |_| {
+                let _ = send(&client, &bus_name);
+            });
+            card.append(&button);
+            button
+        };
+        let previous_button = button(
+            "media-skip-backward-symbolic",
+            "Previous",
+            MprisClient::previous_player,
+        );
+        let play_pause_button = button(
+            "media-playback-start-symbolic",
+            "Play/Pause",
+            MprisClient::play_pause_player,
+        );
+        let next_button = button(
+            "media-skip-forward-symbolic",
+            "Next",
+            MprisClient::next_player,
+        );
+
+        PlayerCard {
+            bus_name: bus_name.to_string(),
+            row: gtk::ListBoxRow::builder().child(&card).build(),
+            art,
+            art_source: None,
+            title_label,
+            artist_label,
+            identity_label,
+            previous_button,
+            play_pause_button,
+            next_button,
+        }
+    }
+
+    /// Show `info` on the card's existing widgets.
+    fn update(&mut self, info: &MediaInfo) {
+        let art_source = Some((info.art_url.clone(), info.desktop_entry.clone()));
+        if self.art_source != art_source {
+            match info.art_url.as_deref().and_then(notification_icon) {
+                Some(icon) => self.art.set_from_gicon(&icon),
+                None => self.art.set_icon_name(Some(
+                    &info
+                        .desktop_entry
+                        .as_deref()
+                        .and_then(player_icon_name)
+                        .unwrap_or_else(|| FALLBACK_PLAYER_ICON.to_string()),
+                )),
+            }
+            self.art_source = art_source;
+        }
+
+        let lines = [
+            (&self.title_label, info.title.as_str()),
+            (&self.artist_label, info.artist.as_str()),
+            (&self.identity_label, info.player_identity.as_str()),
+        ];
+        for (label, text) in lines {
+            label.set_label(text);
+            label.set_visible(!text.trim().is_empty());
+        }
+
+        self.play_pause_button.set_icon_name(match info.status {
+            PlayerStatus::Playing => "media-playback-pause-symbolic",
+            _ => "media-playback-start-symbolic",
+        });
+        self.previous_button.set_sensitive(info.can_go_previous);
+        self.play_pause_button
+            .set_sensitive(info.can_play || info.can_pause);
+        self.next_button.set_sensitive(info.can_go_next);
+    }
+}
+
+/// Clicking out on the progress ring jumps to that point in the track.
+fn setup_ring_seek(
+    content: &MediaContent,
+    client: MprisClient,
+    latest_info: Arc<Mutex<MediaInfo>>,
+) {
+    content
+        .play_pause_button
+        .connect_seek_requested(move |_, fraction| {
+            let (length, track_id) = match latest_info.lock() {
+                Ok(info) => (info.length, info.track_id.clone()),
+                Err(_) => (None, None),
+            };
+            let Some(target) = length.map(|length| length.mul_f64(fraction)) else {
+                return;
+            };
+            let _ = match track_id {
+                Some(track_id) => client.set_track_position(&track_id, target),
+                None => client.set_position(target),
+            };
+        });
+}
+
+/// Scrub without committing: while the seek bar is held, moving it only
+/// previews the target time, and the player is sent a single `set_position`
+/// on release. Keyboard changes seek straight away.
+fn setup_seek_bar(content: &MediaContent, client: MprisClient) {
+    let scale = &content.seek_scale;
+    let dragging = content.seek_dragging.clone();
+
+    scale.connect_change_value({
+        let client = client.clone();
+        let dragging = dragging.clone();
+        let position_button = content.position_button.clone();
+        let elapsed_label = content.seek_elapsed_label.clone();
+        move |scale, _scroll, value| {
+            let adjustment = scale.adjustment();
+            let value = value.clamp(adjustment.lower(), adjustment.upper());
+            if dragging.load(Ordering::SeqCst) {
+                let target = format_time(Duration::from_secs_f64(value));
+                position_button.set_label(&format!(
+                    "{} / {}",
+                    target,
+                    format_time(Duration::from_secs_f64(adjustment.upper()))
+                ));
+                elapsed_label.set_text(&target);
+            } else {
+                let _ = client.set_position(Duration::from_secs_f64(value));
+            }
+            glib::Propagation::Proceed
+        }
+    });
+
+    // Watch raw presses and releases in the capture phase; the scale's own
+    // gestures claim the sequence, so a gesture of ours would be cancelled
+    let press_controller = gtk::EventControllerLegacy::new();
+    press_controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+    press_controller.connect_event({
+        let scale = scale.clone();
+        move |_, event| {
+            match event.event_type() {
+                gtk::gdk::EventType::ButtonPress | gtk::gdk::EventType::TouchBegin => {
+                    dragging.store(true, Ordering::SeqCst);
+                }
+                gtk::gdk::EventType::ButtonRelease
+                | gtk::gdk::EventType::TouchEnd
+                | gtk::gdk::EventType::TouchCancel
+                    if dragging.swap(false, Ordering::SeqCst) =>
+                {
+                    let _ = client.set_position(Duration::from_secs_f64(scale.value()));
+                }
+                _ => {}
+            }
+            glib::Propagation::Proceed
+        }
+    });
+    scale.add_controller(press_controller);
+}
+
+/// Shows `controls` near the pointer while it's over `overlay`, and hides
+/// them when it leaves. They stop following once the pointer gets close,
+/// so it can reach them.
+fn setup_floating_controls(overlay: &gtk::Overlay, controls: &gtk::Box) {
+    let motion = gtk::EventControllerMotion::new();
+
+    let place = {
+        let controls = controls.clone();
+        move |controller: &gtk::EventControllerMotion, x: f64, y: f64| {
+            let Some(overlay) = controller.widget() else {
+                return;
+            };
+            let (_, natural) = controls.preferred_size();
+            let (width, height) = (natural.width() as f64, natural.height() as f64);
+
+            if controls.is_visible() {
+                let left = controls.margin_start() as f64;
+                let top = controls.margin_top() as f64;
+                let near = x >= left - FLOATING_CONTROLS_SLACK
+                    && x <= left + width + FLOATING_CONTROLS_SLACK
+                    && y >= top - FLOATING_CONTROLS_SLACK
+                    && y <= top + height + FLOATING_CONTROLS_SLACK;
+                if near {
+                    return;
+                }
+            }
+
+            // Centered just below the pointer, kept inside the art
+            let max_left = (overlay.width() as f64 - width).max(0.0);
+            let max_top = (overlay.height() as f64 - height).max(0.0);
+            controls.set_margin_start((x - width / 2.0).clamp(0.0, max_left) as i32);
+            controls.set_margin_top((y + FLOATING_CONTROLS_OFFSET).clamp(0.0, max_top) as i32);
+            controls.set_visible(true);
+        }
+    };
+    motion.connect_enter(place.clone());
+    motion.connect_motion(place);
+    motion.connect_leave({
+        let controls = controls.clone();
+        move |_| controls.set_visible(false)
+    });
+
+    overlay.add_controller(motion);
+}
+
+fn setup_background_click(
+    content: &MediaContent,
+    client: MprisClient,
+    action: BackgroundClickAction,
+) {
+    if action == BackgroundClickAction::None {
+        return;
+    }
+
+    // The action waits out the double-click time, so the first click of a
+    // double-click (which raises the player from the art) doesn't also fire
+    let pending: Arc<Mutex<Option<glib::SourceId>>> = Arc::default();
+    let cancel_pending = {
+        let pending = pending.clone();
+        move || {
+            if let Some(source) = pending.lock().ok().and_then(|mut p| p.take()) {
+                source.remove();
+            }
+        }
+    };
+
+    let click_gesture = gtk::GestureClick::new();
+    click_gesture.set_button(gtk::gdk::BUTTON_PRIMARY);
+    click_gesture.connect_pressed({
+        let cancel_pending = cancel_pending.clone();
+        move |_, n_press, _, _| {
+            if n_press > 1 {
+                cancel_pending();
+            }
+        }
+    });
+    click_gesture.connect_cancel({
+        let cancel_pending = cancel_pending.clone();
+        move |_, _| cancel_pending()
+    });
+    click_gesture.connect_released({
+        let container = content.container.clone();
+        move |_, n_press, x, y| {
+            if n_press != 1 || lands_on_interactive_child(&container, x, y) {
+                return;
+            }
+            cancel_pending();
+            let delay = container.settings().gtk_double_click_time().max(0) as u64;
+            let source = glib::timeout_add_local_once(Duration::from_millis(delay), {
+                let client = client.clone();
+                let pending = pending.clone();
+                move || {
+                    if let Ok(mut pending) = pending.lock() {
+                        pending.take();
+                    }
+                    let _ = match action {
+                        BackgroundClickAction::PlayPause => client.play_pause(),
+                        BackgroundClickAction::Raise => client.raise(),
+                        BackgroundClickAction::None => Ok(()),
+                    };
+                }
+            });
+            if let Ok(mut pending) = pending.lock() {
+                *pending = Some(source);
+            }
+        }
+    });
+    content.container.add_controller(click_gesture);
+}
+
+/// Double-clicking the album art brings the player's own window forward.
+/// Players that can't be raised get a toast from the result handler.
+fn setup_art_double_click(content: &MediaContent, client: MprisClient) {
+    let click_gesture = gtk::GestureClick::new();
+    click_gesture.set_button(gtk::gdk::BUTTON_PRIMARY);
+    click_gesture.connect_pressed(move |gesture, n_press, _, _| {
+        if n_press == 2 {
+            gesture.set_state(gtk::EventSequenceState::Claimed);
+            let _ = client.raise();
+        }
+    });
+    content.art_container.add_controller(click_gesture);
+}
+
+/// Whether the point hits a button, slider or other focusable widget inside
+/// `container`, which handle their own clicks.
+fn lands_on_interactive_child(container: &gtk::Box, x: f64, y: f64) -> bool {
+    let mut widget = container.pick(x, y, gtk::PickFlags::DEFAULT);
+    while let Some(w) = widget {
+        if w == *container.upcast_ref::<gtk::Widget>() {
+            return false;
+        }
+        if w.is_focusable() {
+            return true;
+        }
+        widget = w.parent();
+    }
+    false
+}
+
+fn build_main_menu() -> gio::Menu {
+    let menu = gio::Menu::new();
+
+    let players_section = gio::Menu::new();
+    players_section.append(Some("Pause All Players"), Some("win.pause-all"));
+    players_section.append(Some("Resume All Players"), Some("win.play-all"));
+    players_section.append(
+        Some("Stop After This Track"),
+        Some("win.stop-after-current"),
+    );
+    players_section.append(Some("Quit Player"), Some("win.quit-player"));
+    menu.append_section(None, &players_section);
+
+    let share_section = gio::Menu::new();
+    share_section.append(Some("Export Now Playing Card…"), Some("win.export-card"));
+    share_section.append(Some("Copy Now Playing Card"), Some("win.copy-card"));
+    share_section.append(
+        Some("Copy Link at Current Time"),
+        Some("win.copy-timestamped-link"),
+    );
+    menu.append_section(None, &share_section);
+
+    let app_section = gio::Menu::new();
+    app_section.append(Some("Reconnect to Players"), Some("win.reconnect"));
+    app_section.append(Some("Launch at Login"), Some("win.autostart"));
+    app_section.append(Some("Preferences"), Some("win.preferences"));
+    app_section.append(Some("Keyboard Shortcuts"), Some("win.show-shortcuts"));
+    app_section.append(Some("About Empress"), Some("win.about"));
+    app_section.append(Some("Quit"), Some("win.quit"));
+    menu.append_section(None, &app_section);
+
+    menu
+}
+
+/// App-level actions for the buttons on track notifications, which can be
+/// pressed while the window is hidden.
+fn setup_notification_actions(app: &adw::Application, client: MprisClient) {
+    let next_track = gio::SimpleAction::new("next-track", None);
+    next_track.connect_activate({
+        let client = client.clone();
+        move |_, _| {
+            let _ = client.next();
+        }
+    });
+    app.add_action(&next_track);
+
+    let previous_track = gio::SimpleAction::new("previous-track", None);
+    previous_track.connect_activate(move |_, _| {
+        let _ = client.previous();
+    });
+    app.add_action(&previous_track);
+}
+
+/// Show what's now playing, replacing the notification for the last track.
+fn notify_track_change(app: &gtk::Application, info: &MediaInfo) {
+    if info.status != PlayerStatus::Playing || info.title.trim().is_empty() {
+        return;
+    }
+
+    let notification = gio::Notification::new(&info.title);
+    let body = [&info.artist, &info.album]
+        .into_iter()
+        .filter(|field| !field.is_empty())
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" — ");
+    if !body.is_empty() {
+        notification.set_body(Some(&body));
+    }
+    if let Some(icon) = info.art_url.as_deref().and_then(notification_icon) {
+        notification.set_icon(&icon);
+    }
+    if info.can_go_previous {
+        notification.add_button("Previous", "app.previous-track");
+    }
+    if info.can_go_next {
+        notification.add_button("Next", "app.next-track");
+    }
+    app.send_notification(Some("track-change"), &notification);
+}
+
+/// Replace the notification for a track whose art at `url` was still
+/// downloading when it went out, now that the art is in the cache.
+fn renotify_with_art(content: &MediaContent, url: &str) {
+    let Some(info) = content
+        .notified_without_art
+        .lock()
+        .ok()
+        .and_then(|mut notified| notified.take_if(|info| info.art_url.as_deref() == Some(url)))
+    else {
+        return;
+    };
+    let app = content
+        .album_art
+        .root()
+        .and_downcast::<gtk::Window>()
+        .and_then(|window| window.application());
+    if let Some(app) = app {
+        notify_track_change(&app, &info);
+    }
+}
+
+fn is_web_url(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// The art as a notification icon: embedded images as they are, web art
+/// once it's in the cache, and local files.
+fn notification_icon(art_url: &str) -> Option<gio::Icon> {
+    if let Some((_, data)) = art_url
+        .strip_prefix("data:")
+        .and_then(|uri| uri.split_once(";base64,"))
+    {
+        let bytes = glib::Bytes::from_owned(glib::base64_decode(data));
+        return Some(gio::BytesIcon::new(&bytes).upcast());
+    }
+    let path = if is_web_url(art_url) {
+        art_cache::cached_path(art_url)?
+    } else {
+        art_local_path(art_url)?.into()
+    };
+    Some(gio::FileIcon::new(&gio::File::for_path(path)).upcast())
+}
+
+fn setup_actions(
+    window: &adw::ApplicationWindow,
+    content: &MediaContent,
+    client: MprisClient,
+    latest_info: Arc<Mutex<MediaInfo>>,
+    config: &Config,
+) {
+    let pause_all = gio::SimpleAction::new("pause-all", None);
+    pause_all.connect_activate({
+        let client = client.clone();
+        move |_, _| {
+            let _ = client.pause_all();
+        }
+    });
+    window.add_action(&pause_all);
+
+    let play_all = gio::SimpleAction::new("play-all", None);
+    play_all.connect_activate({
+        let client = client.clone();
+        move |_, _| {
+            let _ = client.play_all();
+        }
+    });
+    window.add_action(&play_all);
+
+    let quit_player = gio::SimpleAction::new("quit-player", None);
+    quit_player.connect_activate({
+        let client = client.clone();
+        move |_, _| {
+            let _ = client.quit_player();
+        }
+    });
+    window.add_action(&quit_player);
+
+    // Kept in sync with the player by the update loop, since it disarms
+    // itself once the track ends
+    let stop_after_current =
+        gio::SimpleAction::new_stateful("stop-after-current", None, &false.to_variant());
+    stop_after_current.connect_change_state({
+        let client = client.clone();
+        move |action, value| {
+            let Some(enabled) = value.and_then(|v| v.get::<bool>()) else {
+                return;
+            };
+            client.set_stop_after_current(enabled);
+            action.set_state(&enabled.to_variant());
+        }
+    });
+    window.add_action(&stop_after_current);
+
+    let reconnect = gio::SimpleAction::new("reconnect", None);
+    reconnect.connect_activate({
+        let client = client.clone();
+        move |_, _| {
+            client.reconnect();
+        }
+    });
+    window.add_action(&reconnect);
+
+    let export_card = gio::SimpleAction::new("export-card", None);
+    export_card.connect_activate({
+        let window = window.clone();
+        let album_art = content.album_art.clone();
+        let latest_info = latest_info.clone();
+        move |_, _| {
+            let Some(card) = render_share_card(&album_art, &latest_info) else {
+                return;
+            };
+
+            let dialog = gtk::FileDialog::builder()
+                .title("Export Now Playing Card")
+                .initial_name("now-playing.png")
+                .modal(true)
+                .build();
+            dialog.save(Some(&window), gio::Cancellable::NONE, move |result| {
+                // An error here means the dialog was dismissed
+                let Some(path) = result.ok().and_then(|file| file.path()) else {
+                    return;
+                };
+                if let Err(e) = card.save_to_png(&path) {
+                    eprintln!("Failed to save card to {}: {}", path.display(), e);
+                }
+            });
+        }
+    });
+    window.add_action(&export_card);
+
+    // The track's own URL, with the current position added where the
+    // config knows how, so it opens at the same spot
+    let copy_timestamped_link = gio::SimpleAction::new("copy-timestamped-link", None);
+    copy_timestamped_link.connect_activate({
+        let window = window.clone();
+        let latest_info = latest_info.clone();
+        let config = config.clone();
+        move |_, _| {
+            let Ok(info) = latest_info.lock() else {
+                return;
+            };
+            let Some(ref url) = info.url else {
+                return;
+            };
+            let link = match info.position {
+                Some(position) => config.timestamped_link(url, position),
+                None => url.clone(),
+            };
+            window.clipboard().set_text(&link);
+        }
+    });
+    window.add_action(&copy_timestamped_link);
+
+    let copy_card = gio::SimpleAction::new("copy-card", None);
+    copy_card.connect_activate({
+        let window = window.clone();
+        let album_art = content.album_art.clone();
+        move |_, _| {
+            if let Some(card) = render_share_card(&album_art, &latest_info) {
+                window.clipboard().set_texture(&card);
+            }
+        }
+    });
+    window.add_action(&copy_card);
+
+    // Reflects whether the autostart entry exists on disk, so removing it
+    // by hand shows up the next time the window opens
+    let start_minimized = config.autostart_minimized;
+    let autostart_action =
+        gio::SimpleAction::new_stateful("autostart", None, &autostart::is_enabled().to_variant());
+    autostart_action.connect_change_state(move |action, value| {
+        let Some(enabled) = value.and_then(|v| v.get::<bool>()) else {
+            return;
+        };
+        match autostart::set_enabled(enabled, start_minimized) {
+            Ok(()) => action.set_state(&enabled.to_variant()),
+            Err(e) => eprintln!(
+                "Failed to update autostart entry {}: {}",
+                autostart::path().display(),
+                e
+            ),
+        }
+    });
+    window.add_action(&autostart_action);
+
+    let show_shortcuts = gio::SimpleAction::new("show-shortcuts", None);
+    show_shortcuts.connect_activate({
+        let window = window.clone();
+        move |_, _| show_shortcuts_dialog(&window)
+    });
+    window.add_action(&show_shortcuts);
+
+    let about = gio::SimpleAction::new("about", None);
+    about.connect_activate({
+        let window = window.clone();
+        move |_, _| show_about_dialog(&window)
+    });
+    window.add_action(&about);
+
+    // Closing may only hide the window, so quitting goes through the app
+    let quit = gio::SimpleAction::new("quit", None);
+    quit.connect_activate({
+        let window = window.clone();
+        move |_, _| match window.application() {
+            Some(app) => {
+                // Quitting skips close-request, where the size is saved
+                save_window_size(window.upcast_ref());
+                app.quit()
+            }
+            None => window.close(),
+        }
+    });
+    window.add_action(&quit);
+}
+
+/// Remember the window's size for next launch. Not while maximized or
+/// fullscreen, so the next launch opens at the size it had before that.
+fn save_window_size(window: &gtk::Window) {
+    if window.is_maximized() || window.is_fullscreen() {
+        return;
+    }
+    let (width, height) = window.default_size();
+    State::update(|state| {
+        state.window_width = Some(width);
+        state.window_height = Some(height);
+    });
+}
+
+/// Render the share card for the current track, using the cover that is
+/// on screen. Returns `None` when nothing is playing.
+fn render_share_card(
+    album_art: &gtk::Picture,
+    latest_info: &Mutex<MediaInfo>,
+) -> Option<gdk::Texture> {
+    let info = latest_info.lock().ok()?.clone();
+    if info.title.is_empty() {
+        return None;
+    }
+
+    let art = album_art
+        .paintable()
+        .filter(|_| album_art.is_visible())
+        .and_downcast::<gdk::Texture>();
+
+    match share_card::render(&info, art.as_ref()) {
+        Ok(card) => Some(card),
+        Err(e) => {
+            eprintln!("Failed to render now playing card: {}", e);
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ShortcutAction {
+    Quit,
+    QuitPlayer,
+    Raise,
+    PauseAll,
+    PlayAll,
+    PlayPause,
+    Play,
+    Pause,
+    Previous,
+    Next,
+    Stop,
+    ToggleMute,
+    CopyTrackInfo,
+    ShowShortcuts,
+}
+
+impl ShortcutAction {
+    fn title(self) -> &'static str {
+        match self {
+            ShortcutAction::Quit => "Quit",
+            ShortcutAction::QuitPlayer => "Quit the player",
+            ShortcutAction::Raise => "Show the player's window",
+            ShortcutAction::PauseAll => "Pause all players",
+            ShortcutAction::PlayAll => "Resume all players",
+            ShortcutAction::PlayPause => "Play or pause",
+            ShortcutAction::Play => "Play",
+            ShortcutAction::Pause => "Pause",
+            ShortcutAction::Previous => "Previous track",
+            ShortcutAction::Next => "Next track",
+            ShortcutAction::Stop => "Stop",
+            ShortcutAction::ToggleMute => "Mute or unmute",
+            ShortcutAction::CopyTrackInfo => "Copy the current track",
+            ShortcutAction::ShowShortcuts => "Keyboard shortcuts",
+        }
+    }
+}
+
+/// Every key binding, as GTK accelerators. Both the key handler and the
+/// shortcuts dialog read this, so the dialog can't fall out of date.
+const SHORTCUTS: &[(&str, ShortcutAction)] = &[
+    ("space", ShortcutAction::PlayPause),
+    ("Up", ShortcutAction::Play),
+    ("Down", ShortcutAction::Pause),
+    ("Left", ShortcutAction::Previous),
+    ("Right", ShortcutAction::Next),
+    ("s", ShortcutAction::Stop),
+    ("m", ShortcutAction::ToggleMute),
+    ("<Control><Shift>p", ShortcutAction::PauseAll),
+    ("<Control><Shift>r", ShortcutAction::PlayAll),
+    ("<Control>r", ShortcutAction::Raise),
+    ("<Control>c", ShortcutAction::CopyTrackInfo),
+    ("F1", ShortcutAction::ShowShortcuts),
+    ("<Control>q", ShortcutAction::Quit),
+    ("<Shift>q", ShortcutAction::QuitPlayer),
+];
+
+fn shortcut_action(key: gdk::Key, modifier: gdk::ModifierType) -> Option<ShortcutAction> {
+    // Ignore lock keys and the like
+    let modifier = modifier & gtk::accelerator_get_default_mod_mask();
+    SHORTCUTS.iter().find_map(|(accelerator, action)| {
+        let (accel_key, accel_modifier) = gtk::accelerator_parse(*accelerator)?;
+        (key.to_lower() == accel_key.to_lower() && modifier == accel_modifier).then_some(*action)
+    })
+}
+
+/// "Artist – Title (Album)" for the clipboard, leaving out whatever the
+/// track doesn't have. `None` when nothing is playing.
+fn track_info_line(info: &MediaInfo) -> Option<String> {
+    if info.player_identity.is_empty() || info.title.trim().is_empty() {
+        return None;
+    }
+    let mut line = match info.artist.trim() {
+        "" => info.title.clone(),
+        artist => format!("{} – {}", artist, info.title),
+    };
+    if !info.album.trim().is_empty() {
+        line.push_str(&format!(" ({})", info.album.trim()));
+    }
+    Some(line)
+}
+
+fn setup_keyboard_shortcuts(
+    window: &adw::ApplicationWindow,
+    client: MprisClient,
+    latest_info: Arc<Mutex<MediaInfo>>,
+) {
+    let event_controller = gtk::EventControllerKey::new();
+
+    event_controller.connect_key_pressed({
+        let window = window.clone();
+        move |_, key, _code, modifier| {
+            let Some(action) = shortcut_action(key, modifier) else {
+                return glib::Propagation::Proceed;
+            };
+            // Sliders and text fields with focus keep their use of space
+            let focus_takes_space = GtkWindowExt::focus(&window)
+                .is_some_and(|widget| widget.is::<gtk::Range>() || widget.is::<gtk::Editable>());
+            if action == ShortcutAction::PlayPause && focus_takes_space {
+                return glib::Propagation::Proceed;
+            }
+            match action {
+                ShortcutAction::Quit => {
+                    let _ = WidgetExt::activate_action(&window, "win.quit", None);
+                }
+                ShortcutAction::QuitPlayer => {
+                    let _ = client.quit_player();
+                }
+                ShortcutAction::ShowShortcuts => {
+                    let _ = WidgetExt::activate_action(&window, "win.show-shortcuts", None);
+                }
+                // Bring the player's own window to the front
+                ShortcutAction::Raise => {
+                    let _ = client.raise();
+                }
+                ShortcutAction::PauseAll => {
+                    let _ = client.pause_all();
+                }
+                ShortcutAction::PlayAll => {
+                    let _ = client.play_all();
+                }
+                ShortcutAction::PlayPause => {
+                    let _ = client.play_pause();
+                }
+                ShortcutAction::Play => {
+                    let _ = client.play();
+                }
+                ShortcutAction::Pause => {
+                    let _ = client.pause();
+                }
+                ShortcutAction::Previous => {
+                    let _ = client.previous();
+                }
+                ShortcutAction::Next => {
+                    let _ = client.next();
+                }
+                ShortcutAction::Stop => {
+                    let _ = client.stop();
+                }
+                ShortcutAction::ToggleMute => {
+                    let _ = client.toggle_mute();
+                }
+                ShortcutAction::CopyTrackInfo => {
+                    let line = latest_info
+                        .lock()
+                        .ok()
+                        .and_then(|info| track_info_line(&info));
+                    if let Some(line) = line {
+                        WidgetExt::display(&window).clipboard().set_text(&line);
+                    }
+                }
+            }
+            glib::Propagation::Stop
+        }
+    });
+
+    window.add_controller(event_controller);
+}
+
+/// List every entry in `SHORTCUTS` in a dialog over `window`.
+fn show_shortcuts_dialog(window: &adw::ApplicationWindow) {
+    let group = adw::PreferencesGroup::new();
+    for (accelerator, action) in SHORTCUTS {
+        let row = adw::ActionRow::builder().title(action.title()).build();
+        row.add_suffix(
+            &gtk::ShortcutLabel::builder()
+                .accelerator(*accelerator)
+                .valign(gtk::Align::Center)
+                .build(),
+        );
+        group.add(&row);
+    }
+
+    let page = adw::PreferencesPage::new();
+    page.add(&group);
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&adw::HeaderBar::new());
+    toolbar_view.set_content(Some(&page));
+
+    let dialog = adw::Dialog::builder()
+        .title("Keyboard Shortcuts")
+        .content_width(360)
+        .child(&toolbar_view)
+        .build();
+    dialog.present(Some(window));
+}
+
+fn setup_preferences(
+    window: &adw::ApplicationWindow,
+    content: &MediaContent,
+    client: MprisClient,
+    live_config: Arc<Mutex<Config>>,
+) {
+    let preferences = gio::SimpleAction::new("preferences", None);
+    preferences.connect_activate({
+        let window = window.clone();
+        let backdrop = content.backdrop.clone();
+        move |_, _| show_preferences_dialog(&window, &backdrop, &client, &live_config)
+    });
+    window.add_action(&preferences);
+}
+
+/// Change a setting for the running app and in the config file.
+fn change_config(live_config: &Mutex<Config>, f: impl Fn(&mut Config)) {
+    if let Ok(mut config) = live_config.lock() {
+        f(&mut config);
+    }
+    Config::update(f);
+}
+
+/// Like `change_config`, but the file is only written once the value has
+/// stopped changing for a moment, so holding down a spin button's arrow
+/// doesn't rewrite it on every step. `pending` holds the scheduled write.
+fn change_config_debounced(
+    live_config: &Mutex<Config>,
+    pending: &Arc<Mutex<Option<glib::SourceId>>>,
+    f: impl Fn(&mut Config) + 'static,
+) {
+    if let Ok(mut config) = live_config.lock() {
+        f(&mut config);
+    }
+    let Ok(mut scheduled) = pending.lock() else {
+        return;
+    };
+    if let Some(source) = scheduled.take() {
+        source.remove();
+    }
+    *scheduled = Some(glib::timeout_add_local_once(CONFIG_SAVE_DELAY, {
+        let pending = pending.clone();
+        move || {
+            if let Ok(mut scheduled) = pending.lock() {
+                scheduled.take();
+            }
+            Config::update(f);
+        }
+    }));
 }
 
-fn setup_controls(content: &MediaContent, client: MprisClient) {
-    content.play_pause_button.button().connect_clicked({
-        let client = client.clone();
-        move |_| {
-            let _ = client.play_pause();
-        }
-    });
+/// The settings that are worth changing without editing the config file.
+/// Each one is saved as soon as it changes and applies straight away.
+fn show_preferences_dialog(
+    window: &adw::ApplicationWindow,
+    backdrop: &gtk::Picture,
+    client: &MprisClient,
+    live_config: &Arc<Mutex<Config>>,
+) {
+    let Ok(config) = live_config.lock().map(|config| config.clone()) else {
+        return;
+    };
 
-    content.next_button.connect_clicked({
-        let client = client.clone();
-        move |_| {
-            let _ = client.next();
+    let seek_step = adw::SpinRow::with_range(1.0, 60.0, 1.0);
+    seek_step.set_title("Scroll Seek Step");
+    seek_step.set_subtitle("Seconds per scroll over the controls");
+    seek_step.set_value(config.scroll_seek_seconds as f64);
+    seek_step.connect_value_notify({
+        let live_config = live_config.clone();
+        let pending = Arc::default();
+        move |row| {
+            let seconds = row.value() as u64;
+            change_config_debounced(&live_config, &pending, move |config| {
+                config.scroll_seek_seconds = seconds
+            });
         }
     });
 
-    content.prev_button.connect_clicked({
-        let client = client.clone();
-        move |_| {
-            let _ = client.previous();
+    let shift_seek_step = adw::SpinRow::with_range(1.0, 600.0, 5.0);
+    shift_seek_step.set_title("Shift+Scroll Seek Step");
+    shift_seek_step.set_subtitle("Seconds per scroll while Shift is held");
+    shift_seek_step.set_value(config.scroll_seek_shift_seconds as f64);
+    shift_seek_step.connect_value_notify({
+        let live_config = live_config.clone();
+        let pending = Arc::default();
+        move |row| {
+            let seconds = row.value() as u64;
+            change_config_debounced(&live_config, &pending, move |config| {
+                config.scroll_seek_shift_seconds = seconds
+            });
         }
     });
 
-    // Volume slider → MPRIS
-    content.volume_scale.connect_value_changed({
-        let client = client.clone();
-        move |scale| {
-            let _ = client.set_volume(scale.value());
+    let natural_scroll = adw::SwitchRow::builder()
+        .title("Natural Scrolling")
+        .subtitle("Scrolling down seeks forward")
+        .active(config.natural_scroll)
+        .build();
+    natural_scroll.connect_active_notify({
+        let live_config = live_config.clone();
+        move |row| {
+            let active = row.is_active();
+            change_config(&live_config, |config| config.natural_scroll = active);
         }
     });
 
-    // Add scroll event handler for seeking
-    let scroll_controller =
-        gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::VERTICAL);
-
-    scroll_controller.connect_scroll({
-        let client = client.clone();
-        move |_, _dx, dy| {
-            // dy > 0 means scrolling down (go back 5 seconds)
-            // dy < 0 means scrolling up (go forward 5 seconds)
-            let offset_seconds = if dy > 0.0 { -5 } else { 5 };
-
-            // MPRIS seek uses microseconds
-            let offset_micros = offset_seconds * 1_000_000;
-            let _ = client.seek(offset_micros);
+    let seeking = adw::PreferencesGroup::builder().title("Seeking").build();
+    seeking.add(&seek_step);
+    seeking.add(&shift_seek_step);
+    seeking.add(&natural_scroll);
 
-            glib::Propagation::Stop
+    let notifications = adw::SwitchRow::builder()
+        .title("Track Change Notifications")
+        .subtitle("When the track changes while the window isn't focused")
+        .active(config.track_notifications)
+        .build();
+    notifications.connect_active_notify({
+        let live_config = live_config.clone();
+        move |row| {
+            let active = row.is_active();
+            change_config(&live_config, |config| config.track_notifications = active);
         }
     });
 
-    content.play_pause_button.add_controller(scroll_controller);
-}
+    let art_backdrop = adw::SwitchRow::builder()
+        .title("Blurred Art Background")
+        .subtitle("Show the album art behind the window")
+        .active(config.art_backdrop)
+        .build();
+    art_backdrop.connect_active_notify({
+        let live_config = live_config.clone();
+        let backdrop = backdrop.clone();
+        move |row| {
+            let active = row.is_active();
+            backdrop.set_visible(active);
+            change_config(&live_config, |config| config.art_backdrop = active);
+        }
+    });
 
-fn setup_keyboard_shortcuts(window: &adw::ApplicationWindow, client: MprisClient) {
-    let event_controller = gtk::EventControllerKey::new();
+    let notifications_group = adw::PreferencesGroup::builder()
+        .title("Notifications")
+        .build();
+    notifications_group.add(&notifications);
+    let appearance = adw::PreferencesGroup::builder().title("Appearance").build();
+    appearance.add(&art_backdrop);
 
-    event_controller.connect_key_pressed({
+    // Comma-separated, applied with the row's apply button
+    let player_priority = adw::EntryRow::builder()
+        .title("Preferred Players, Most Preferred First")
+        .text(config.player_priority.join(", "))
+        .show_apply_button(true)
+        .build();
+    player_priority.connect_apply({
+        let live_config = live_config.clone();
         let client = client.clone();
-        let window = window.clone();
-        move |_, key, _code, modifier| {
-            // Ctrl+Q to quit
-            if key == gtk::gdk::Key::q && modifier == gtk::gdk::ModifierType::CONTROL_MASK {
-                window.close();
-                return glib::Propagation::Stop;
-            }
-
-            // Up arrow to play
-            if key == gtk::gdk::Key::Up && modifier.is_empty() {
-                let _ = client.play_pause();
-                return glib::Propagation::Stop;
-            }
-
-            // Down arrow to pause
-            if key == gtk::gdk::Key::Down && modifier.is_empty() {
-                let _ = client.play_pause();
-                return glib::Propagation::Stop;
-            }
+        move |row| {
+            let priority: Vec<String> = row
+                .text()
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect();
+            client.set_player_priority(priority.clone());
+            change_config(&live_config, |config| {
+                config.player_priority = priority.clone()
+            });
+        }
+    });
 
-            // Left arrow for previous
-            if key == gtk::gdk::Key::Left && modifier.is_empty() {
-                let _ = client.previous();
-                return glib::Propagation::Stop;
-            }
+    let players = adw::PreferencesGroup::builder()
+        .title("Players")
+        .description("Picked in Auto mode when several players are running")
+        .build();
+    players.add(&player_priority);
 
-            // Right arrow for next
-            if key == gtk::gdk::Key::Right && modifier.is_empty() {
-                let _ = client.next();
-                return glib::Propagation::Stop;
-            }
+    let page = adw::PreferencesPage::new();
+    page.add(&seeking);
+    page.add(&notifications_group);
+    page.add(&appearance);
+    page.add(&players);
 
-            glib::Propagation::Proceed
-        }
-    });
+    let dialog = adw::PreferencesDialog::new();
+    dialog.add(&page);
+    dialog.present(Some(window));
+}
 
-    window.add_controller(event_controller);
+fn show_about_dialog(window: &adw::ApplicationWindow) {
+    let repository = env!("CARGO_PKG_REPOSITORY");
+    let dialog = adw::AboutDialog::builder()
+        .application_name("Empress")
+        .application_icon(APP_ID)
+        .version(env!("CARGO_PKG_VERSION"))
+        .developer_name("toasterrepairman")
+        .license_type(gtk::License::Gpl30)
+        .website(repository)
+        .issue_url(format!("{}/issues", repository))
+        .build();
+    dialog.present(Some(window));
 }
 
 fn build_sidebar() -> SidebarContent {
@@ -862,6 +3703,27 @@ fn build_sidebar() -> SidebarContent {
         .width_request(200)
         .build();
 
+    // Player-specific extras; hidden unless the player exposes any
+    let details_header = gtk::Label::builder()
+        .label("Player Details")
+        .css_classes(vec!["heading"])
+        .halign(gtk::Align::Start)
+        .margin_bottom(6)
+        .build();
+
+    let details_list = gtk::ListBox::builder()
+        .css_classes(vec!["boxed-list"])
+        .selection_mode(gtk::SelectionMode::None)
+        .build();
+
+    let details_box = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .margin_bottom(12)
+        .visible(false)
+        .build();
+    details_box.append(&details_header);
+    details_box.append(&details_list);
+
     let header_label = gtk::Label::builder()
         .label("Session History")
         .css_classes(vec!["heading"])
@@ -882,15 +3744,123 @@ fn build_sidebar() -> SidebarContent {
         .propagate_natural_width(true)
         .build();
 
+    container.append(&details_box);
     container.append(&header_label);
     container.append(&scrolled_window);
 
     SidebarContent {
         container,
         list_box,
+        details_box,
+        details_list,
+    }
+}
+
+fn update_player_details(sidebar: &SidebarContent, properties: &[(&'static str, String)]) {
+    while let Some(child) = sidebar.details_list.first_child() {
+        sidebar.details_list.remove(&child);
+    }
+
+    for (label, value) in properties {
+        let row = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(6)
+            .margin_start(8)
+            .margin_end(8)
+            .margin_top(6)
+            .margin_bottom(6)
+            .build();
+
+        let name_label = gtk::Label::builder()
+            .label(*label)
+            .halign(gtk::Align::Start)
+            .hexpand(true)
+            .build();
+
+        let value_label = gtk::Label::builder()
+            .label(value)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .max_width_chars(SIDEBAR_LABEL_MAX_WIDTH_CHARS)
+            .halign(gtk::Align::End)
+            .opacity(0.7)
+            .build();
+
+        row.append(&name_label);
+        row.append(&value_label);
+        sidebar.details_list.append(&row);
+    }
+
+    sidebar.details_box.set_visible(!properties.is_empty());
+}
+
+fn update_play_history(list_box: &gtk::ListBox, tracks: &VecDeque<PlayedTrack>) {
+    while let Some(child) = list_box.first_child() {
+        list_box.remove(&child);
+    }
+
+    for track in tracks {
+        let row = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(6)
+            .margin_start(8)
+            .margin_end(8)
+            .margin_top(6)
+            .margin_bottom(6)
+            .tooltip_text("Click to copy")
+            .build();
+
+        let text_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(2)
+            .hexpand(true)
+            .build();
+
+        let title_label = gtk::Label::builder()
+            .label(&track.title)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .max_width_chars(SIDEBAR_LABEL_MAX_WIDTH_CHARS)
+            .halign(gtk::Align::Start)
+            .build();
+        text_box.append(&title_label);
+
+        if !track.artist.is_empty() {
+            let artist_label = gtk::Label::builder()
+                .label(&track.artist)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .max_width_chars(SIDEBAR_LABEL_MAX_WIDTH_CHARS)
+                .halign(gtk::Align::Start)
+                .opacity(0.7)
+                .build();
+            text_box.append(&artist_label);
+        }
+
+        let time_label = gtk::Label::builder()
+            .label(format_played_at(track.played_at))
+            .valign(gtk::Align::Start)
+            .css_classes(vec!["caption", "numeric"])
+            .opacity(0.7)
+            .build();
+
+        row.append(&text_box);
+        row.append(&time_label);
+        list_box.append(&row);
     }
 }
 
+/// Time of day for tracks played today, otherwise the date as well.
+fn format_played_at(played_at: u64) -> String {
+    let Ok(time) = glib::DateTime::from_unix_local(played_at as i64) else {
+        return String::new();
+    };
+    let today = glib::DateTime::now_local()
+        .map(|now| now.ymd() == time.ymd())
+        .unwrap_or(false);
+    let format = if today { "%H:%M" } else { "%b %e, %H:%M" };
+    time.format(format)
+        .map(|s| s.to_string())
+        .unwrap_or_default()
+}
+
 fn update_sidebar(list_box: &gtk::ListBox, history: &VecDeque<StatusHistoryEntry>) {
     // Clear existing children
     while let Some(child) = list_box.first_child() {
@@ -917,6 +3887,7 @@ fn update_sidebar(list_box: &gtk::ListBox, history: &VecDeque<StatusHistoryEntry
         let title_label = gtk::Label::builder()
             .label(&entry.title)
             .ellipsize(gtk::pango::EllipsizeMode::End)
+            .max_width_chars(SIDEBAR_LABEL_MAX_WIDTH_CHARS)
             .halign(gtk::Align::Start)
             .css_classes(vec!["title-3"])
             .build();
@@ -924,6 +3895,7 @@ fn update_sidebar(list_box: &gtk::ListBox, history: &VecDeque<StatusHistoryEntry
         let artist_label = gtk::Label::builder()
             .label(&entry.artist)
             .ellipsize(gtk::pango::EllipsizeMode::End)
+            .max_width_chars(SIDEBAR_LABEL_MAX_WIDTH_CHARS)
             .halign(gtk::Align::Start)
             .opacity(0.7)
             .build();
@@ -949,3 +3921,596 @@ fn update_sidebar(list_box: &gtk::ListBox, history: &VecDeque<StatusHistoryEntry
         list_box.append(&row);
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use gtk::subclass::prelude::ObjectSubclassIsExt;
+
+    /// Run `f` on the shared GTK test thread. GTK needs a display: run the
+    /// suite under `xvfb-run` or with `GDK_BACKEND=broadway` to exercise it
+    /// headless, or set `EMPRESS_SKIP_GTK_TESTS` to skip these tests.
+    pub(crate) fn with_gtk<F>(f: F)
+    where
+        F: FnOnce() + Send + std::panic::UnwindSafe + 'static,
+    {
+        let has_display = ["DISPLAY", "WAYLAND_DISPLAY", "GDK_BACKEND"]
+            .iter()
+            .any(|var| std::env::var_os(var).is_some());
+        if !has_display {
+            if std::env::var_os("EMPRESS_SKIP_GTK_TESTS").is_some() {
+                eprintln!("Skipping GTK test: EMPRESS_SKIP_GTK_TESTS is set");
+                return;
+            }
+            panic!("GTK tests need a display; set EMPRESS_SKIP_GTK_TESTS to skip them");
+        }
+
+        gtk::test_synced(move || {
+            adw::init().expect("Failed to initialize libadwaita");
+            f();
+        });
+    }
+
+    #[test]
+    fn playing_track_fills_labels_and_shows_pause() {
+        with_gtk(|| {
+            let content = build_content(&Config::default());
+            let info = MediaInfo {
+                title: "Song".to_string(),
+                artist: "artist".to_string(),
+                album: "Album".to_string(),
+                status: PlayerStatus::Playing,
+                position: Some(Duration::from_secs(30)),
+                length: Some(Duration::from_secs(120)),
+                ..Default::default()
+            };
+
+            update_ui_widgets(&content, &info, true);
+
+            assert_eq!(content.title_label.text(), "Song");
+            assert_eq!(content.artist_label.text(), "artist");
+            assert_eq!(content.album_label.text(), "Album");
+            assert!(content.artist_label.is_visible());
+            assert!(content.album_label.is_visible());
+            assert_eq!(content.placeholder_label.text(), "A");
+
+            assert_eq!(
+                content.play_pause_button.icon_name().as_deref(),
+                Some("media-playback-pause-symbolic")
+            );
+            let inner_button = content
+                .play_pause_button
+                .first_child()
+                .and_downcast::<gtk::Button>()
+                .unwrap();
+            assert!(!inner_button.has_css_class("paused"));
+            assert_eq!(content.play_pause_button.imp().progress.get(), 0.25);
+        });
+    }
+
+    #[test]
+    fn ring_style_comes_from_the_config() {
+        with_gtk(|| {
+            let config = Config {
+                ring_width: 5.0,
+                ring_color: Some("#ff0000".to_string()),
+                ..Default::default()
+            };
+            let ring = build_content(&config).play_pause_button;
+            assert_eq!(ring.property::<f64>("ring-width"), 5.0);
+            assert_eq!(
+                ring.property::<Option<gdk::RGBA>>("ring-color"),
+                Some(gdk::RGBA::RED)
+            );
+
+            // Unset follows the accent color
+            let ring = build_content(&Config::default()).play_pause_button;
+            assert_eq!(ring.property::<Option<gdk::RGBA>>("ring-color"), None);
+        });
+    }
+
+    #[test]
+    fn ring_spins_while_remote_art_downloads() {
+        with_gtk(|| {
+            let content = build_content(&Config::default());
+            let info = MediaInfo {
+                title: "Song".to_string(),
+                art_url: Some("https://example.invalid/never-cached.jpg".to_string()),
+                ..Default::default()
+            };
+
+            update_ui_widgets(&content, &info, true);
+            assert!(content.play_pause_button.is_loading());
+
+            // Moving on to a track without art stops it
+            update_ui_widgets(&content, &MediaInfo::default(), true);
+            assert!(!content.play_pause_button.is_loading());
+        });
+    }
+
+    #[test]
+    fn stopped_track_empties_the_ring() {
+        with_gtk(|| {
+            let content = build_content(&Config::default());
+            let info = MediaInfo {
+                title: "Song".to_string(),
+                status: PlayerStatus::Stopped,
+                position: Some(Duration::from_secs(30)),
+                length: Some(Duration::from_secs(120)),
+                ..Default::default()
+            };
+
+            update_ui_widgets(&content, &info, true);
+            assert_eq!(content.play_pause_button.imp().progress.get(), 0.0);
+            assert_eq!(
+                content.play_pause_button.icon_name().as_deref(),
+                Some("media-playback-start-symbolic")
+            );
+        });
+    }
+
+    #[test]
+    fn players_without_an_icon_use_the_generic_one() {
+        with_gtk(|| {
+            let entry = player_entry(&AvailablePlayer {
+                identity: "Player".to_string(),
+                desktop_entry: Some("no-such-player-entry".to_string()),
+            });
+            assert_eq!(entry.identity(), "Player");
+            assert_eq!(entry.icon_name(), FALLBACK_PLAYER_ICON);
+        });
+    }
+
+    #[test]
+    fn malformed_embedded_art_shows_the_placeholder() {
+        with_gtk(|| {
+            let content = build_content(&Config::default());
+            let info = MediaInfo {
+                title: "Song".to_string(),
+                art_url: Some("data:image/png;base64,not*an*image".to_string()),
+                ..Default::default()
+            };
+
+            update_ui_widgets(&content, &info, true);
+            assert!(content.album_art.paintable().is_none());
+            assert!(content.placeholder.is_visible());
+        });
+    }
+
+    #[test]
+    fn queue_is_hidden_without_a_track_list() {
+        with_gtk(|| {
+            let content = build_content(&Config::default());
+            let track = |title: &str| QueuedTrack {
+                id: format!("/track/{}", title),
+                title: title.to_string(),
+                artist: "Artist".to_string(),
+                length: None,
+            };
+
+            update_queue(&content, true, Some(&[track("One"), track("Two")]));
+            assert!(content.queue_expander.is_visible());
+            assert_eq!(content.queue_list.n_items(), 2);
+            assert_eq!(content.queue_list.string(1).unwrap(), "Two — Artist");
+
+            // Collapsed, the queue isn't read but the expander stays
+            update_queue(&content, true, None);
+            assert!(content.queue_expander.is_visible());
+            assert_eq!(content.queue_list.n_items(), 2);
+
+            update_queue(&content, false, None);
+            assert!(!content.queue_expander.is_visible());
+            assert_eq!(content.queue_list.n_items(), 0);
+        });
+    }
+
+    #[test]
+    fn notification_icons_come_from_local_or_embedded_art() {
+        let icon = notification_icon("file:///music/cover%20art.jpg")
+            .and_downcast::<gio::FileIcon>()
+            .unwrap();
+        assert_eq!(
+            icon.file().path().unwrap(),
+            std::path::Path::new("/music/cover art.jpg")
+        );
+        assert!(notification_icon("data:image/png;base64,iVBORw0KGgo=")
+            .and_downcast::<gio::BytesIcon>()
+            .is_some());
+        assert!(notification_icon("https://example.invalid/never-cached.jpg").is_none());
+    }
+
+    #[test]
+    fn player_cards_only_offer_what_the_player_allows() {
+        with_gtk(|| {
+            let info = MediaInfo {
+                title: "Song".to_string(),
+                player_identity: "Player".to_string(),
+                status: PlayerStatus::Playing,
+                can_pause: true,
+                can_go_next: true,
+                ..Default::default()
+            };
+            let mut card = PlayerCard::new(":1.42", &MprisClient::new());
+            card.update(&info);
+
+            assert!(card.next_button.is_sensitive());
+            assert!(card.play_pause_button.is_sensitive());
+            assert_eq!(
+                card.play_pause_button.icon_name().as_deref(),
+                Some("media-playback-pause-symbolic")
+            );
+            assert!(!card.previous_button.is_sensitive());
+            assert!(!card.artist_label.is_visible());
+        });
+    }
+
+    #[test]
+    fn player_cards_update_in_place() {
+        with_gtk(|| {
+            let mut card = PlayerCard::new(":1.42", &MprisClient::new());
+            let mut info = MediaInfo {
+                title: "Song".to_string(),
+                status: PlayerStatus::Playing,
+                can_pause: true,
+                ..Default::default()
+            };
+            card.update(&info);
+            let play_pause = card.play_pause_button.clone();
+
+            info.title = "Next song".to_string();
+            info.status = PlayerStatus::Paused;
+            card.update(&info);
+
+            assert_eq!(card.play_pause_button, play_pause);
+            assert_eq!(card.title_label.label(), "Next song");
+            assert_eq!(
+                play_pause.icon_name().as_deref(),
+                Some("media-playback-start-symbolic")
+            );
+        });
+    }
+
+    #[test]
+    fn volume_icon_follows_the_level() {
+        assert_eq!(volume_icon_name(0.0), "audio-volume-muted-symbolic");
+        assert_eq!(volume_icon_name(0.2), "audio-volume-low-symbolic");
+        assert_eq!(volume_icon_name(0.5), "audio-volume-medium-symbolic");
+        assert_eq!(volume_icon_name(1.0), "audio-volume-high-symbolic");
+    }
+
+    #[test]
+    fn copied_track_info_leaves_out_missing_fields() {
+        let info = MediaInfo {
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            album: "Album".to_string(),
+            player_identity: "Player".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            track_info_line(&info).as_deref(),
+            Some("Artist – Song (Album)")
+        );
+
+        let info = MediaInfo {
+            artist: String::new(),
+            album: String::new(),
+            ..info
+        };
+        assert_eq!(track_info_line(&info).as_deref(), Some("Song"));
+        assert_eq!(track_info_line(&MediaInfo::default()), None);
+    }
+
+    #[test]
+    fn album_caption_adds_track_and_disc_numbers() {
+        let info = MediaInfo {
+            album: "Album".to_string(),
+            track_number: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(album_caption(&info), "Album • Track 3");
+
+        let info = MediaInfo {
+            disc_number: Some(2),
+            track_number: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(album_caption(&info), "Disc 2 • Track 3");
+        assert_eq!(album_caption(&MediaInfo::default()), "");
+    }
+
+    #[test]
+    fn lost_player_shows_nothing_playing() {
+        with_gtk(|| {
+            let content = build_content(&Config::default());
+            let info = MediaInfo {
+                title: "Song".to_string(),
+                artist: "Artist".to_string(),
+                player_identity: "Player".to_string(),
+                ..Default::default()
+            };
+
+            update_ui_widgets(&content, &info, true);
+            update_ui_widgets(&content, &MediaInfo::default(), true);
+            assert_eq!(content.title_label.text(), "No media playing");
+            assert!(!content.artist_label.is_visible());
+        });
+    }
+
+    #[test]
+    fn navigation_follows_what_the_player_allows() {
+        with_gtk(|| {
+            let content = build_content(&Config::default());
+            let info = MediaInfo {
+                title: "Radio".to_string(),
+                can_control: true,
+                can_pause: true,
+                can_go_next: true,
+                ..Default::default()
+            };
+
+            update_ui_widgets(&content, &info, true);
+            assert!(!content.prev_button.is_sensitive());
+            assert!(content.next_button.is_sensitive());
+            assert!(content.play_pause_button.is_sensitive());
+
+            update_ui_widgets(&content, &MediaInfo::default(), true);
+            assert!(!content.play_pause_button.is_sensitive());
+        });
+    }
+
+    #[test]
+    fn seek_bar_is_greyed_out_for_streams() {
+        with_gtk(|| {
+            let config = Config {
+                show_seek_bar: true,
+                ..Default::default()
+            };
+            let content = build_content(&config);
+            let info = MediaInfo {
+                title: "Radio".to_string(),
+                position: Some(Duration::from_secs(75)),
+                can_seek: true,
+                ..Default::default()
+            };
+
+            update_progress(&content, &info);
+            assert!(content.seek_row.is_visible());
+            assert!(!content.seek_scale.is_sensitive());
+            assert_eq!(content.seek_elapsed_label.text(), "1:15");
+            assert_eq!(content.seek_length_label.text(), "-:--");
+        });
+    }
+
+    #[test]
+    fn repeat_is_greyed_out_without_loop_support() {
+        with_gtk(|| {
+            let content = build_content(&Config::default());
+            let mut info = MediaInfo {
+                title: "Song".to_string(),
+                can_control: true,
+                ..Default::default()
+            };
+
+            update_ui_widgets(&content, &info, true);
+            assert!(content.repeat_button.is_visible());
+            assert!(!content.repeat_button.is_sensitive());
+
+            info.loop_mode = Some(LoopMode::Track);
+            update_ui_widgets(&content, &info, true);
+            assert!(content.repeat_button.is_sensitive());
+            assert_eq!(
+                content.repeat_button.icon_name().as_deref(),
+                Some("media-playlist-repeat-song-symbolic")
+            );
+        });
+    }
+
+    #[test]
+    fn paused_track_hides_empty_fields_and_shows_play() {
+        with_gtk(|| {
+            let content = build_content(&Config::default());
+            let info = MediaInfo {
+                title: "Stream".to_string(),
+                status: PlayerStatus::Paused,
+                position: Some(Duration::from_secs(30)),
+                ..Default::default()
+            };
+
+            update_ui_widgets(&content, &info, true);
+
+            assert!(!content.artist_label.is_visible());
+            assert!(!content.album_label.is_visible());
+
+            assert_eq!(
+                content.play_pause_button.icon_name().as_deref(),
+                Some("media-playback-start-symbolic")
+            );
+            let inner_button = content
+                .play_pause_button
+                .first_child()
+                .and_downcast::<gtk::Button>()
+                .unwrap();
+            assert!(inner_button.has_css_class("paused"));
+            // No length means no progress to show.
+            assert_eq!(content.play_pause_button.imp().progress.get(), 0.0);
+        });
+    }
+
+    #[test]
+    fn missing_art_shows_placeholder() {
+        with_gtk(|| {
+            let content = build_content(&Config::default());
+            let info = MediaInfo {
+                title: "untitled".to_string(),
+                ..Default::default()
+            };
+
+            update_ui_widgets(&content, &info, true);
+
+            assert!(content.art_container.is_visible());
+            assert!(content.placeholder.is_visible());
+            assert!(content.placeholder_label.is_visible());
+            assert!(!content.album_art.is_visible());
+            assert_eq!(content.placeholder_label.text(), "U");
+        });
+    }
+
+    #[test]
+    fn placeholder_without_a_track_shows_an_icon() {
+        with_gtk(|| {
+            let content = build_content(&Config::default());
+            let info = MediaInfo {
+                player_identity: "Player".to_string(),
+                desktop_entry: Some("no-such-player-entry".to_string()),
+                ..Default::default()
+            };
+
+            update_ui_widgets(&content, &info, true);
+
+            assert!(content.placeholder.is_visible());
+            assert!(!content.placeholder_label.is_visible());
+            assert!(content.placeholder_icon.is_visible());
+            assert_eq!(
+                content.placeholder_icon.icon_name().as_deref(),
+                Some(FALLBACK_ART_ICON)
+            );
+        });
+    }
+
+    #[test]
+    fn compact_line_skips_empty_fields() {
+        let info = MediaInfo {
+            title: "Song".to_string(),
+            album: "Album".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(compact_metadata_line(&info), "Song • Album");
+    }
+
+    #[test]
+    fn time_round_trips_through_format_and_parse() {
+        assert_eq!(format_time(Duration::from_secs(65)), "1:05");
+        assert_eq!(format_time(Duration::from_secs(3725)), "1:02:05");
+        assert_eq!(parse_time("1:05"), Some(Duration::from_secs(65)));
+        assert_eq!(parse_time(" 1:02:05 "), Some(Duration::from_secs(3725)));
+        assert_eq!(parse_time("42"), Some(Duration::from_secs(42)));
+    }
+
+    #[test]
+    fn malformed_times_are_rejected() {
+        assert_eq!(parse_time(""), None);
+        assert_eq!(parse_time("1:5"), None);
+        assert_eq!(parse_time("1:60"), None);
+        assert_eq!(parse_time("a:05"), None);
+        assert_eq!(parse_time("1:00:00:00"), None);
+    }
+
+    #[test]
+    fn playlist_remaining_includes_rest_of_current_track() {
+        let info = MediaInfo {
+            position: Some(Duration::from_secs(60)),
+            length: Some(Duration::from_secs(240)),
+            queued_after: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        };
+        assert_eq!(playlist_remaining(&info), Some(Duration::from_secs(3780)));
+        assert_eq!(format_minutes(Duration::from_secs(3780)), "1 h 03 min");
+        assert_eq!(playlist_remaining(&MediaInfo::default()), None);
+    }
+
+    #[test]
+    fn shortcuts_match_their_accelerators() {
+        with_gtk(|| {
+            let ctrl_shift = gdk::ModifierType::CONTROL_MASK | gdk::ModifierType::SHIFT_MASK;
+            assert_eq!(
+                shortcut_action(gdk::Key::P, ctrl_shift),
+                Some(ShortcutAction::PauseAll)
+            );
+            assert_eq!(
+                shortcut_action(gdk::Key::r, gdk::ModifierType::CONTROL_MASK),
+                Some(ShortcutAction::Raise)
+            );
+            assert_eq!(
+                shortcut_action(gdk::Key::Up, gdk::ModifierType::LOCK_MASK),
+                Some(ShortcutAction::Play)
+            );
+            assert_eq!(
+                shortcut_action(gdk::Key::Q, gdk::ModifierType::SHIFT_MASK),
+                Some(ShortcutAction::QuitPlayer)
+            );
+            // Down always pauses rather than toggling
+            assert_eq!(
+                shortcut_action(gdk::Key::Down, gdk::ModifierType::empty()),
+                Some(ShortcutAction::Pause)
+            );
+            assert_eq!(
+                shortcut_action(gdk::Key::space, gdk::ModifierType::empty()),
+                Some(ShortcutAction::PlayPause)
+            );
+            assert_eq!(
+                shortcut_action(gdk::Key::space, gdk::ModifierType::CONTROL_MASK),
+                None
+            );
+            assert_eq!(
+                shortcut_action(gdk::Key::a, gdk::ModifierType::empty()),
+                None
+            );
+        });
+    }
+
+    #[test]
+    fn backdrop_is_shrunk_and_blurred() {
+        // A 100×50 image, white on the left half and black on the right
+        let (width, height) = (100, 50);
+        let mut pixels = vec![0u8; width * height * 4];
+        for y in 0..height {
+            for x in 0..width / 2 {
+                pixels[(y * width + x) * 4..][..4].copy_from_slice(&[255; 4]);
+            }
+        }
+        let (blurred, small_width, small_height) =
+            shrink_and_blur(&pixels, width, height, width * 4);
+        assert_eq!((small_width, small_height), (20, 10));
+        assert_eq!(blurred.len(), small_width * small_height * 4);
+        // The far edges keep their colour; the middle is softened into grey
+        assert_eq!(blurred[0], 255);
+        assert_eq!(blurred[(small_width - 1) * 4], 0);
+        let middle = blurred[(small_width / 2) * 4];
+        assert!(middle > 0 && middle < 255);
+    }
+
+    #[test]
+    fn rates_snap_to_the_nearest_choice() {
+        assert_eq!(nearest_rate_index(PLAYBACK_RATES, 1.0), 1);
+        assert_eq!(nearest_rate_index(PLAYBACK_RATES, 1.3), 2);
+        assert_eq!(nearest_rate_index(PLAYBACK_RATES, 3.0), 4);
+    }
+
+    #[test]
+    fn rates_outside_the_players_range_are_left_out() {
+        assert_eq!(playback_rates(Some((0.5, 1.5))), [0.75, 1.0, 1.25, 1.5]);
+        assert_eq!(playback_rates(Some((1.0, 1.0))), [1.0]);
+        assert_eq!(playback_rates(None), PLAYBACK_RATES);
+    }
+
+    #[test]
+    fn ratings_round_to_whole_stars() {
+        assert_eq!(rating_stars(0.0), 0);
+        assert_eq!(rating_stars(0.6), 3);
+        assert_eq!(rating_stars(0.75), 4);
+        assert_eq!(rating_stars(1.0), 5);
+    }
+
+    #[test]
+    fn window_title_leads_with_position() {
+        let info = MediaInfo {
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            position: Some(Duration::from_secs(83)),
+            ..Default::default()
+        };
+        assert_eq!(window_title_with_position(&info), "1:23 Song — Artist");
+        assert_eq!(window_title_with_position(&MediaInfo::default()), "Empress");
+    }
+}
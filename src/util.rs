@@ -0,0 +1,23 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// Format as `M:SS`, or `H:MM:SS` past an hour.
+pub fn format_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// Replace the file at `path` with `contents` by writing a temporary file
+/// next to it and renaming it over, so readers (and a crash) never see a
+/// half-written file.
+pub fn write_atomically(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let temp = path.with_extension("tmp");
+    fs::write(&temp, contents)?;
+    fs::rename(&temp, path)
+}
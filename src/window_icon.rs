@@ -0,0 +1,62 @@
+use gtk::gdk;
+use gtk::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const ICON_SIZE: i32 = 128;
+
+// GTK only caches icon lookups by name, so every cover gets a new one
+static ICON_SERIAL: AtomicUsize = AtomicUsize::new(0);
+
+/// Where the generated icons live. Added to the icon theme's search path.
+fn icon_dir() -> PathBuf {
+    glib::user_cache_dir().join("empress").join("window-icon")
+}
+
+/// Use `art`, scaled down, as the window icon, or go back to the app icon
+/// when there is none. Only shows up where the desktop takes per-window
+/// icons (mostly X11 taskbars and window switchers).
+pub fn set_from_art(window: &gtk::Window, art: Option<&gdk::Texture>) -> anyhow::Result<()> {
+    let dir = icon_dir();
+    // Only the current cover is kept around
+    if let Ok(entries) = fs::read_dir(&dir) {
+        for entry in entries.flatten() {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+
+    let Some(texture) = art else {
+        window.set_icon_name(None);
+        return Ok(());
+    };
+
+    let stream = gio::MemoryInputStream::from_bytes(&texture.save_to_png_bytes());
+    let pixbuf = gdk_pixbuf::Pixbuf::from_stream_at_scale(
+        &stream,
+        ICON_SIZE,
+        ICON_SIZE,
+        true,
+        gio::Cancellable::NONE,
+    )?;
+
+    fs::create_dir_all(&dir)?;
+    let name = format!(
+        "empress-cover-{}",
+        ICON_SERIAL.fetch_add(1, Ordering::SeqCst)
+    );
+    pixbuf.savev(dir.join(format!("{}.png", name)), "png", &[])?;
+
+    // Setting the search path, even to the same list, makes the theme
+    // rescan it and pick up the new file
+    let theme = gtk::IconTheme::for_display(&WidgetExt::display(window));
+    let mut search_path = theme.search_path();
+    if !search_path.contains(&dir) {
+        search_path.push(dir);
+    }
+    let search_path: Vec<_> = search_path.iter().map(|p| p.as_path()).collect();
+    theme.set_search_path(&search_path);
+
+    window.set_icon_name(Some(&name));
+    Ok(())
+}